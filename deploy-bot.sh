@@ -9,6 +9,7 @@ echo "Creating new 'Secrets.toml'..."
 cp ./secrets/deploy/Secrets.toml ./Secrets.toml
 
 echo "[WARNING]: Resetting a server will destory all persisted data. Do not use lightly!"
+echo "Run /backup_state in Discord first, /restore_state can load it back afterwards."
 echo "Reset Server Before Deployment? (y/n): "
 read should_restart_server
 
@@ -1,6 +1,7 @@
 use crate::checks::is_master_admin;
 use crate::checks::is_on_admin_list;
 use crate::extensions::InteractiveSnowflakeExt;
+use crate::message_component_interactions::member_history_menu::MemberHistoryMenu;
 use crate::state::role_backups::RoleBackups;
 use crate::state::t_rooms::TRooms;
 use crate::state::Data;
@@ -43,7 +44,7 @@ pub async fn archeage_apply(
 
     let http = ctx.serenity_context().http();
     let needs_to_apply_role = ctx.data().needs_to_apply_role.as_ref();
-    let needs_to_apply_role = RoleId::from_str(&needs_to_apply_role).unwrap();
+    let needs_to_apply_role = RoleId::from_str(needs_to_apply_role)?;
     let has_role = user
         .has_role(http, ctx.guild_id().unwrap(), needs_to_apply_role)
         .await?;
@@ -58,7 +59,7 @@ pub async fn archeage_apply(
     let res = match res {
         Some(ref modal_data) => {
             let needs_to_apply_channel = ctx.data().needs_to_apply_channel.as_ref();
-            let needs_to_apply_channel = ChannelId::from_str(&needs_to_apply_channel)?;
+            let needs_to_apply_channel = ChannelId::from_str(needs_to_apply_channel)?;
 
             let member = ctx.guild_id().unwrap();
             let mut member = member.member(http, user.id).await?;
@@ -79,11 +80,7 @@ pub async fn archeage_apply(
                             )
                             .set_author(author)
                             .field("Discord Username", &member.user.name, true)
-                            .field(
-                                "Display Name",
-                                format!("{}", member.user.id.get_interactive(),),
-                                true,
-                            )
+                            .field("Display Name", member.user.id.get_interactive(), true)
                             .field("In-Game Name", nickname, false)
                     })
                 })
@@ -127,8 +124,7 @@ pub async fn triggered(
         let mut member = member.member(http, user.id).await?;
 
         // Store all user roles into state
-        let mut role_backups = RoleBackups::load(ctx.data())?;
-        let success = role_backups.add(ctx.data(), user.id, &member.roles)?;
+        let success = RoleBackups::add(ctx.data(), &ctx.into(), user.id, &member.roles).await?;
 
         if success {
             // Remove all current roles
@@ -140,10 +136,8 @@ pub async fn triggered(
                 .add_role(http, RoleId::from_str(ctx.data().triggered_role.as_str())?)
                 .await?;
 
-            // Get the current state of all TRooms
-            let mut t_rooms = TRooms::load(ctx.data())?;
-
-            let open_room = t_rooms.find_open_room(ctx.data())?;
+            // Claim the first open room out of all TRooms
+            let open_room = TRooms::find_open_room(ctx.data()).await?;
 
             if let Some(open_room) = open_room {
                 let role = open_room.0;
@@ -196,16 +190,14 @@ pub async fn release_trigger(
         let mut member = member.member(http, user.id).await?;
         let author = ctx.author();
         // Extract, then delete stored user roles
-        let mut role_backups = RoleBackups::load(ctx.data())?;
-        let extracted_roles = role_backups.remove(ctx.data(), user.id)?;
+        let extracted_roles = RoleBackups::remove(ctx.data(), &ctx.into(), user.id).await?;
 
         if let Some(extracted_roles) = extracted_roles {
             // Remove currently assigned roles
             // TODO: Extend `Member` to have a method that wipes all roles, this is better than the .clone() hack
             member.remove_roles(http, &member.roles.clone()).await?;
 
-            let extracted_roles: Vec<RoleId> =
-                extracted_roles.into_iter().map(|x| RoleId(x)).collect();
+            let extracted_roles: Vec<RoleId> = extracted_roles.into_iter().map(RoleId).collect();
 
             // Assign extracted roles
             member.add_roles(http, &extracted_roles).await?;
@@ -225,3 +217,16 @@ pub async fn release_trigger(
 
     Ok(())
 }
+
+/// "What has this user been up to?"
+#[poise::command(
+    ephemeral,
+    check = "is_on_admin_list",
+    context_menu_command = "User History"
+)]
+pub async fn user_history(
+    ctx: Context<'_>,
+    #[description = "The looked up user"] user: serenity::User,
+) -> Result<(), Error> {
+    MemberHistoryMenu::open(ctx, &user).await
+}
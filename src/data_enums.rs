@@ -5,6 +5,15 @@ pub enum CustomId {
     PickGamesAddExecute,
     PickGamesRemoveExecute,
     GuildApply,
+    StateRestoreConfirm,
+    StateRestoreCancel,
+    QuarantineApprove,
+    QuarantineKick,
+    QuarantineBan,
+    MemberHistoryNewer,
+    MemberHistoryOlder,
+    GameSuggestionAccept,
+    GameSuggestionDecline,
     #[default]
     Invalid,
 }
@@ -17,11 +26,38 @@ impl CustomId {
             "pick-games-add-execute" => Self::PickGamesAddExecute,
             "pick-games-remove-execute" => Self::PickGamesRemoveExecute,
             "guild-apply" => Self::GuildApply,
+            "state-restore-confirm" => Self::StateRestoreConfirm,
+            "state-restore-cancel" => Self::StateRestoreCancel,
+            "quarantine-approve" => Self::QuarantineApprove,
+            "quarantine-kick" => Self::QuarantineKick,
+            "quarantine-ban" => Self::QuarantineBan,
+            "member-history-newer" => Self::MemberHistoryNewer,
+            "member-history-older" => Self::MemberHistoryOlder,
+            "game-suggestion-accept" => Self::GameSuggestionAccept,
+            "game-suggestion-decline" => Self::GameSuggestionDecline,
             _ => Self::Invalid,
         }
     }
 }
 
+impl CustomId {
+    /// Components living on a message that was sent for a single user, these need an
+    /// `ActiveCollectors` session to be answered
+    pub fn is_session_bound(&self) -> bool {
+        matches!(
+            self,
+            Self::PickGamesAddExecute
+                | Self::PickGamesRemoveExecute
+                | Self::StateRestoreConfirm
+                | Self::StateRestoreCancel
+                | Self::MemberHistoryNewer
+                | Self::MemberHistoryOlder
+                | Self::GameSuggestionAccept
+                | Self::GameSuggestionDecline
+        )
+    }
+}
+
 impl std::fmt::Display for CustomId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let display_output: String = match *self {
@@ -30,6 +66,15 @@ impl std::fmt::Display for CustomId {
             Self::PickGamesAddExecute => "pick-games-add-execute".into(),
             Self::PickGamesRemoveExecute => "pick-games-remove-execute".into(),
             Self::GuildApply => "guild-apply".into(),
+            Self::StateRestoreConfirm => "state-restore-confirm".into(),
+            Self::StateRestoreCancel => "state-restore-cancel".into(),
+            Self::QuarantineApprove => "quarantine-approve".into(),
+            Self::QuarantineKick => "quarantine-kick".into(),
+            Self::QuarantineBan => "quarantine-ban".into(),
+            Self::MemberHistoryNewer => "member-history-newer".into(),
+            Self::MemberHistoryOlder => "member-history-older".into(),
+            Self::GameSuggestionAccept => "game-suggestion-accept".into(),
+            Self::GameSuggestionDecline => "game-suggestion-decline".into(),
             Self::Invalid => "__invalid__".into(),
         };
 
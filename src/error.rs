@@ -0,0 +1,211 @@
+use crate::extensions::InteractiveSnowflakeExt;
+use crate::state::{Data, StoreError};
+use crate::utils::embed::truncate;
+use chrono::Utc;
+use poise::serenity_prelude::{
+    self as serenity, colours::branding::RED, ChannelId, Interaction, InteractionResponseType,
+    MessageComponentInteraction,
+};
+use poise::FrameworkError;
+use std::str::FromStr;
+
+/// Every error a command, check or event handler can return
+#[derive(Debug)]
+pub enum BotError {
+    /// Bad input or a request that can't be fulfilled, the message is shown to the user as is
+    User(String),
+    /// The user isn't allowed to do this, the message is shown to the user as is
+    Permission(String),
+    /// A secret is missing or malformed
+    Config(String),
+    /// Boxed, `serenity::Error` is large enough to bloat every `Result` in the crate
+    Discord(Box<serenity::Error>),
+    State(anyhow::Error),
+    /// Anything else, like a failing event sink or HTTP client
+    Internal(anyhow::Error),
+}
+
+impl BotError {
+    /// Whether this error points at a bug or outage, rather than something the user did
+    fn is_unexpected(&self) -> bool {
+        !matches!(self, Self::User(_) | Self::Permission(_))
+    }
+
+    fn user_message(&self) -> String {
+        match self {
+            Self::User(message) | Self::Permission(message) => message.clone(),
+            Self::Config(_) => "The bot is misconfigured, an admin has been notified...".into(),
+            Self::Discord(_) => "Discord rejected the request, please try again...".into(),
+            Self::State(_) => "Bot state could not be read or written, please try again...".into(),
+            Self::Internal(_) => "Something went wrong, please try again...".into(),
+        }
+    }
+}
+
+impl std::fmt::Display for BotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::User(message) => write!(f, "User error: {}", message),
+            Self::Permission(message) => write!(f, "Permission error: {}", message),
+            Self::Config(message) => write!(f, "Config error: {}", message),
+            Self::Discord(e) => write!(f, "Discord error: {}", e),
+            Self::State(e) => write!(f, "State error: {}", e),
+            Self::Internal(e) => write!(f, "Internal error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for BotError {}
+
+impl From<serenity::Error> for BotError {
+    fn from(e: serenity::Error) -> Self {
+        Self::Discord(Box::new(e))
+    }
+}
+
+/// Only errors that started out as a `StoreError` are state errors
+impl From<anyhow::Error> for BotError {
+    fn from(e: anyhow::Error) -> Self {
+        match e.downcast::<StoreError>() {
+            Ok(e) => Self::State(e.into()),
+            Err(e) => Self::Internal(e),
+        }
+    }
+}
+
+impl From<StoreError> for BotError {
+    fn from(e: StoreError) -> Self {
+        Self::State(e.into())
+    }
+}
+
+impl From<serenity::RoleIdParseError> for BotError {
+    fn from(e: serenity::RoleIdParseError) -> Self {
+        Self::Config(format!("Invalid role id: {}", e))
+    }
+}
+
+impl From<serenity::ChannelIdParseError> for BotError {
+    fn from(e: serenity::ChannelIdParseError) -> Self {
+        Self::Config(format!("Invalid channel id: {}", e))
+    }
+}
+
+/// Parses a channel id secret, `name` is only used for the error message
+pub fn channel_from_secret(value: &str, name: &str) -> Result<ChannelId, BotError> {
+    ChannelId::from_str(value)
+        .map_err(|_| BotError::Config(format!("{} secret could not be parsed into a u64", name)))
+}
+
+pub async fn on_error(error: FrameworkError<'_, Data, BotError>) {
+    match error {
+        FrameworkError::Command { error, ctx } => {
+            if error.is_unexpected() {
+                let origin = format!(
+                    "`/{}` used by {}",
+                    ctx.command().qualified_name,
+                    ctx.author().id.get_interactive()
+                );
+                report(ctx.serenity_context(), ctx.data(), &origin, &error).await;
+            }
+
+            let reply = ctx
+                .send(|b| b.content(error.user_message()).ephemeral(true))
+                .await;
+
+            if let Err(e) = reply {
+                tracing::error!("Could not send error reply: {}", e);
+            }
+        }
+        FrameworkError::CommandCheckFailed {
+            error: Some(error),
+            ctx,
+        } => {
+            let reply = ctx
+                .send(|b| b.content(error.user_message()).ephemeral(true))
+                .await;
+
+            if let Err(e) = reply {
+                tracing::error!("Could not send error reply: {}", e);
+            }
+        }
+        FrameworkError::EventHandler {
+            error,
+            ctx,
+            event,
+            framework,
+        } => {
+            if error.is_unexpected() {
+                let origin = format!("`{}` event", event.name());
+                report(ctx, framework.user_data, &origin, &error).await;
+            }
+
+            if let poise::Event::InteractionCreate {
+                interaction: Interaction::MessageComponent(interaction),
+            } = event
+            {
+                reply_to_component(ctx, interaction, &error.user_message()).await;
+            }
+        }
+        error => {
+            if let Err(e) = poise::builtins::on_error(error).await {
+                tracing::error!("Error while handling error: {}", e);
+            }
+        }
+    }
+}
+
+/// Tells the user who pressed a component what went wrong, the handler may already have responded
+async fn reply_to_component(
+    ctx: &serenity::Context,
+    interaction: &MessageComponentInteraction,
+    message: &str,
+) {
+    let response = interaction
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|m| m.content(message).ephemeral(true))
+        })
+        .await;
+
+    if response.is_err() {
+        let followup = interaction
+            .create_followup_message(&ctx.http, |m| m.content(message).ephemeral(true))
+            .await;
+
+        if let Err(e) = followup {
+            tracing::error!("Could not send error reply: {}", e);
+        }
+    }
+}
+
+/// Logs an unexpected error and posts it to the major events channel
+async fn report(ctx: &serenity::Context, data: &Data, origin: &str, error: &BotError) {
+    tracing::error!("{} failed: {}", origin, error);
+
+    let target_channel =
+        match channel_from_secret(&data.major_events_channel, "MAJOR_EVENTS_CHANNEL") {
+            Ok(channel) => channel,
+            Err(e) => {
+                tracing::error!("{}", e);
+                return;
+            }
+        };
+
+    let result = target_channel
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.title("Unexpected Error")
+                    .color(RED)
+                    .description(origin)
+                    .field("Error", truncate(&error.to_string()), false)
+                    .timestamp(Utc::now())
+            })
+        })
+        .await;
+
+    if let Err(e) = result {
+        tracing::error!("Could not post error to the log channel: {}", e);
+    }
+}
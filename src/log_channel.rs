@@ -1,2 +1,51 @@
+use crate::state::{log_routes::LogRoutes, Data};
+use dispatcher::{Coalesce, LogJob};
+use poise::serenity_prelude::CreateEmbed;
+use sinks::LogRecord;
+
+pub mod attribution;
+pub mod dispatcher;
+pub mod guild_events;
+pub mod invite_tracking;
+pub mod message_events;
+pub mod sinks;
+pub mod startup_events;
+pub mod thread_events;
 pub mod user_events;
 pub mod voice_events;
+
+/// Queues a log embed for every channel the record's kind is routed to,
+/// and the record itself for every event sink
+pub fn post_embed(data: &Data, record: LogRecord, embed: CreateEmbed) -> Result<(), crate::Error> {
+    queue(data, record, embed, None)
+}
+
+/// Like `post_embed`, but posts sharing `coalesce.key` in quick succession are merged into one embed
+pub fn post_coalesced_embed(
+    data: &Data,
+    record: LogRecord,
+    embed: CreateEmbed,
+    coalesce: Coalesce,
+) -> Result<(), crate::Error> {
+    queue(data, record, embed, Some(coalesce))
+}
+
+fn queue(
+    data: &Data,
+    record: LogRecord,
+    embed: CreateEmbed,
+    coalesce: Option<Coalesce>,
+) -> Result<(), crate::Error> {
+    let kind = record.kind;
+    data.event_sinks.emit(data, record)?;
+
+    for channel_id in LogRoutes::targets(data, kind)? {
+        data.log_dispatcher.enqueue(LogJob {
+            channel_id,
+            embed: embed.clone(),
+            coalesce: coalesce.clone(),
+        });
+    }
+
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use chrono::Utc;
+use poise::serenity_prelude::{
+    self as serenity,
+    audit_log::{Action, AuditLogEntry, Change, MemberAction, ThreadAction},
+    ChannelId, CreateEmbed, GuildId, UserId,
+};
+use std::time::Duration;
+
+use crate::extensions::InteractiveSnowflakeExt;
+
+/// Audit log entries show up a moment after the gateway event, so the lookup is retried
+const LOOKUP_ATTEMPTS: u32 = 3;
+const LOOKUP_DELAY: Duration = Duration::from_secs(1);
+
+/// Entries older than this belong to an earlier change
+const MAX_ENTRY_AGE_SECONDS: i64 = 30;
+
+#[derive(Clone, Copy)]
+pub enum AuditedChange {
+    Ban,
+    Roles,
+    Nickname,
+    Mute,
+    Deafen,
+    Timeout,
+    ThreadArchive,
+    ThreadLock,
+    ThreadDelete,
+}
+
+/// Who made a change, according to the guild audit log
+pub enum Attribution {
+    Moderator(UserId, Option<String>),
+    SelfMade,
+    Unknown,
+}
+
+impl AuditedChange {
+    fn action(&self) -> Action {
+        match self {
+            Self::Ban => Action::Member(MemberAction::BanAdd),
+            Self::Roles => Action::Member(MemberAction::RoleUpdate),
+            Self::Nickname | Self::Mute | Self::Deafen | Self::Timeout => {
+                Action::Member(MemberAction::Update)
+            }
+            Self::ThreadArchive | Self::ThreadLock => Action::Thread(ThreadAction::Update),
+            Self::ThreadDelete => Action::Thread(ThreadAction::Delete),
+        }
+    }
+
+    /// `MemberAction::Update` also covers timeouts, mutes etc. and `ThreadAction::Update` renames
+    fn matches(&self, entry: &AuditLogEntry) -> bool {
+        let has_change = |f: fn(&Change) -> bool| {
+            entry
+                .changes
+                .as_ref()
+                .map(|changes| changes.iter().any(f))
+                .unwrap_or(false)
+        };
+
+        match self {
+            Self::Nickname => has_change(|x| matches!(x, Change::Nick { .. })),
+            Self::Mute => has_change(|x| matches!(x, Change::Mute { .. })),
+            Self::Deafen => has_change(|x| matches!(x, Change::Deaf { .. })),
+            Self::Timeout => has_change(|x| matches!(x, Change::CommunicationDisabledUntil { .. })),
+            Self::ThreadArchive => has_change(|x| matches!(x, Change::Archived { .. })),
+            Self::ThreadLock => has_change(|x| matches!(x, Change::Locked { .. })),
+            _ => true,
+        }
+    }
+}
+
+impl Attribution {
+    pub async fn lookup(
+        ctx: &serenity::Context,
+        guild_id: GuildId,
+        change: AuditedChange,
+        target: UserId,
+    ) -> Self {
+        Self::find(ctx, guild_id, change, target.0, Some(target)).await
+    }
+
+    /// Like `lookup`, but for a change to a channel, which is never self-made
+    pub async fn lookup_channel(
+        ctx: &serenity::Context,
+        guild_id: GuildId,
+        change: AuditedChange,
+        target: ChannelId,
+    ) -> Self {
+        Self::find(ctx, guild_id, change, target.0, None).await
+    }
+
+    async fn find(
+        ctx: &serenity::Context,
+        guild_id: GuildId,
+        change: AuditedChange,
+        target: u64,
+        target_user: Option<UserId>,
+    ) -> Self {
+        for attempt in 0..LOOKUP_ATTEMPTS {
+            if attempt > 0 {
+                tokio::time::sleep(LOOKUP_DELAY).await;
+            }
+
+            let logs = guild_id
+                .audit_logs(&ctx.http, Some(change.action().num()), None, None, Some(10))
+                .await;
+
+            let logs = match logs {
+                Ok(logs) => logs,
+                Err(e) => {
+                    // Most likely the bot is missing the VIEW_AUDIT_LOG permission
+                    tracing::warn!("Could not read the audit log: {}", e);
+                    return Self::Unknown;
+                }
+            };
+
+            let now = Utc::now().timestamp();
+            let entry = logs.entries.into_iter().find(|entry| {
+                entry.target_id == Some(target)
+                    && now - entry.id.created_at().unix_timestamp() <= MAX_ENTRY_AGE_SECONDS
+                    && change.matches(entry)
+            });
+
+            if let Some(entry) = entry {
+                if Some(entry.user_id) == target_user {
+                    return Self::SelfMade;
+                }
+
+                return Self::Moderator(entry.user_id, entry.reason);
+            }
+        }
+
+        Self::Unknown
+    }
+
+    pub fn moderator(&self) -> Option<UserId> {
+        match self {
+            Self::Moderator(moderator, _) => Some(*moderator),
+            _ => None,
+        }
+    }
+
+    pub fn reason(&self) -> Option<&str> {
+        match self {
+            Self::Moderator(_, reason) => reason.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Adds the "Moderator" and "Reason" fields to a log embed
+    pub fn add_fields(&self, embed: &mut CreateEmbed) {
+        match self {
+            Self::Moderator(moderator, reason) => {
+                let reason = reason.as_deref().unwrap_or("*No reason given*");
+                embed
+                    .field("Moderator", moderator.get_interactive(), true)
+                    .field("Reason", reason, true);
+            }
+            Self::SelfMade => {
+                embed
+                    .field("Moderator", "*Self-made change*", true)
+                    .field("Reason", "*None*", true);
+            }
+            Self::Unknown => {
+                embed
+                    .field("Moderator", "*Unknown, no audit log entry found*", true)
+                    .field("Reason", "*Unknown*", true);
+            }
+        }
+    }
+}
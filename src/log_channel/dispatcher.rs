@@ -0,0 +1,193 @@
+use poise::serenity_prelude::{self as serenity, ChannelId, CreateEmbed, Http};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+use crate::utils::embed::DESCRIPTION_LIMIT;
+
+/// Posts waiting to be sent, anything past this is dropped instead of piling up
+const QUEUE_SIZE: usize = 500;
+
+const MAX_ATTEMPTS: u32 = 5;
+const FIRST_BACKOFF: Duration = Duration::from_secs(1);
+
+/// How long coalescable posts are held back to collect follow ups
+const COALESCE_WINDOW: Duration = Duration::from_secs(5);
+
+pub struct LogJob {
+    pub channel_id: ChannelId,
+    pub embed: CreateEmbed,
+    pub coalesce: Option<Coalesce>,
+}
+
+/// Posts sharing a key and channel within `COALESCE_WINDOW` are merged into one embed
+#[derive(Clone)]
+pub struct Coalesce {
+    pub key: String,
+    /// Title of the merged embed
+    pub title: String,
+    /// This post's line in the merged embed
+    pub line: String,
+}
+
+struct Group {
+    deadline: Instant,
+    jobs: Vec<LogJob>,
+    timestamps: Vec<i64>,
+}
+
+/// Hands log posts to a background task, so event handlers never wait on Discord
+pub struct LogDispatcher(mpsc::Sender<LogJob>);
+
+impl LogDispatcher {
+    pub fn start(http: Arc<Http>) -> Self {
+        let (sender, receiver) = mpsc::channel(QUEUE_SIZE);
+        tokio::spawn(run(http, receiver));
+
+        Self(sender)
+    }
+
+    pub fn enqueue(&self, job: LogJob) {
+        if let Err(e) = self.0.try_send(job) {
+            tracing::warn!("Log post dropped: {}", e);
+        }
+    }
+}
+
+async fn run(http: Arc<Http>, mut receiver: mpsc::Receiver<LogJob>) {
+    let mut groups: Vec<Group> = vec![];
+
+    loop {
+        let next_deadline = groups.iter().map(|x| x.deadline).min();
+
+        let received = match next_deadline {
+            Some(deadline) => match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(received) => received,
+                Err(_) => {
+                    flush(&http, &mut groups, Instant::now()).await;
+                    continue;
+                }
+            },
+            None => receiver.recv().await,
+        };
+
+        let Some(job) = received else {
+            // Every sender is gone, the bot is shutting down
+            flush(&http, &mut groups, Instant::now() + COALESCE_WINDOW).await;
+            return;
+        };
+
+        let Some(coalesce) = &job.coalesce else {
+            send(&http, job.channel_id, job.embed).await;
+            continue;
+        };
+
+        let now = chrono::Utc::now().timestamp();
+        let group = groups.iter_mut().find(|x| {
+            let first = &x.jobs[0];
+            first.channel_id == job.channel_id
+                && first.coalesce.as_ref().map(|x| &x.key) == Some(&coalesce.key)
+        });
+
+        match group {
+            Some(group) => {
+                group.jobs.push(job);
+                group.timestamps.push(now);
+            }
+            None => groups.push(Group {
+                deadline: Instant::now() + COALESCE_WINDOW,
+                jobs: vec![job],
+                timestamps: vec![now],
+            }),
+        }
+    }
+}
+
+/// Sends every group whose window closed before `until`
+async fn flush(http: &Http, groups: &mut Vec<Group>, until: Instant) {
+    let (due, waiting): (Vec<Group>, Vec<Group>) =
+        groups.drain(..).partition(|x| x.deadline <= until);
+    *groups = waiting;
+
+    for group in due {
+        let channel_id = group.jobs[0].channel_id;
+        send(http, channel_id, merge(group)).await;
+    }
+}
+
+/// A single post is sent as is, several are listed in one embed based on the first one
+fn merge(mut group: Group) -> CreateEmbed {
+    if group.jobs.len() == 1 {
+        return group.jobs.remove(0).embed;
+    }
+
+    let first = &group.jobs[0];
+    let title = first
+        .coalesce
+        .as_ref()
+        .map(|x| x.title.clone())
+        .unwrap_or_default();
+
+    let mut lines = String::new();
+    for (job, timestamp) in group.jobs.iter().zip(group.timestamps.iter()) {
+        let line = job.coalesce.as_ref().map(|x| x.line.as_str()).unwrap_or("");
+        let line = format!("<t:{}:T> {}\n", timestamp, line);
+
+        if lines.len() + line.len() > DESCRIPTION_LIMIT {
+            break;
+        }
+        lines.push_str(&line);
+    }
+
+    let mut embed = CreateEmbed::default();
+    for key in ["author", "footer", "color"] {
+        if let Some(value) = first.embed.0.get(key) {
+            embed.0.insert(key, value.clone());
+        }
+    }
+
+    embed
+        .title(format!("{} ({} events)", title, group.jobs.len()))
+        .description(lines)
+        .timestamp(chrono::Utc::now());
+
+    embed
+}
+
+/// Retries with exponential backoff, client errors other than rate limits are not retried
+async fn send(http: &Http, channel_id: ChannelId, embed: CreateEmbed) {
+    let mut backoff = FIRST_BACKOFF;
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        let result = channel_id
+            .send_message(http, |m| m.set_embed(embed.clone()))
+            .await;
+
+        let e = match result {
+            Ok(_) => return,
+            Err(e) => e,
+        };
+
+        let retryable = match &e {
+            serenity::Error::Http(http_error) => match http_error.status_code() {
+                Some(status) => status.as_u16() == 429 || status.is_server_error(),
+                None => true,
+            },
+            _ => false,
+        };
+
+        if !retryable || attempt == MAX_ATTEMPTS {
+            tracing::error!(
+                "Log post to {} failed after {} attempt(s): {}",
+                channel_id,
+                attempt,
+                e
+            );
+            return;
+        }
+
+        tokio::time::sleep(backoff).await;
+        backoff *= 2;
+    }
+}
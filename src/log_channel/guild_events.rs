@@ -0,0 +1,566 @@
+use chrono::Utc;
+use poise::serenity_prelude::{
+    colours::branding::{GREEN, RED, YELLOW},
+    Channel, ChannelCategory, ChannelId, CreateEmbed, GuildChannel, PermissionOverwrite,
+    PermissionOverwriteType, Permissions, Role, RoleId,
+};
+
+use crate::{
+    error::channel_from_secret,
+    extensions::InteractiveSnowflakeExt,
+    log_channel::{dispatcher::LogJob, post_embed, sinks::LogRecord},
+    state::{
+        game_activity::GameActivity,
+        games::Games,
+        guild_apply::GuildApply,
+        log_routes::{LogKind, LogRoutes},
+        quarantine::Quarantine,
+        t_rooms::TRooms,
+        Data, SnowflakeHashmapStorage, SnowflakeStorage,
+    },
+    utils::embed::{describe_parent, footer, truncate, truncate_to, FIELD_LIMIT},
+};
+
+/// The parts of a channel or category the log cares about
+#[derive(Clone, Debug)]
+pub struct ChannelSnapshot {
+    pub id: ChannelId,
+    pub name: String,
+    pub kind: String,
+    pub parent_id: Option<ChannelId>,
+    pub topic: Option<String>,
+    pub overwrites: Vec<PermissionOverwrite>,
+}
+
+impl From<&GuildChannel> for ChannelSnapshot {
+    fn from(channel: &GuildChannel) -> Self {
+        Self {
+            id: channel.id,
+            name: channel.name.clone(),
+            kind: channel.kind.name().to_string(),
+            parent_id: channel.parent_id,
+            topic: channel.topic.clone(),
+            overwrites: channel.permission_overwrites.clone(),
+        }
+    }
+}
+
+impl From<&ChannelCategory> for ChannelSnapshot {
+    fn from(category: &ChannelCategory) -> Self {
+        Self {
+            id: category.id,
+            name: category.name.clone(),
+            kind: category.kind.name().to_string(),
+            parent_id: category.parent_id,
+            topic: None,
+            overwrites: category.permission_overwrites.clone(),
+        }
+    }
+}
+
+impl ChannelSnapshot {
+    /// What structured log sinks see of the channel, overwrites are left out
+    fn record_value(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "type": self.kind,
+            "parent_id": self.parent_id.map(|x| x.0.to_string()),
+            "topic": self.topic,
+        })
+    }
+
+    /// Private channels are not part of the guild, so they have no snapshot
+    pub fn from_channel(channel: &Channel) -> Option<Self> {
+        match channel {
+            Channel::Guild(channel) => Some(channel.into()),
+            Channel::Category(category) => Some(category.into()),
+            _ => None,
+        }
+    }
+}
+
+pub enum GuildEvent {
+    ChannelCreated(ChannelSnapshot),
+    ChannelUpdated(Option<ChannelSnapshot>, ChannelSnapshot),
+    ChannelDeleted(ChannelSnapshot),
+    RoleCreated(Role),
+    RoleUpdated(Option<Role>, Role),
+    RoleDeleted(RoleId, Option<Role>),
+}
+
+// Log channel functionality
+impl GuildEvent {
+    fn execute_channel_created_log(
+        data: &Data,
+        channel: &ChannelSnapshot,
+    ) -> Result<(), crate::Error> {
+        let mut e = CreateEmbed::default();
+        e.title("Channel Created")
+            .color(GREEN)
+            .description(channel.id.get_interactive())
+            .timestamp(Utc::now())
+            .field("Name", &channel.name, true)
+            .field("Type", &channel.kind, true)
+            .field("Category", describe_parent(channel.parent_id), true)
+            .set_footer(footer(format!("Channel ID: {}", channel.id)));
+
+        if !channel.overwrites.is_empty() {
+            e.field(
+                "Permission Overwrites",
+                overwrite_diff(&[], &channel.overwrites),
+                false,
+            );
+        }
+
+        let record = LogRecord::new(LogKind::ChannelCreate)
+            .channel(channel.id)
+            .change(None::<u64>, channel.record_value());
+
+        post_embed(data, record, e)?;
+
+        Ok(())
+    }
+
+    fn execute_channel_updated_log(
+        data: &Data,
+        old: &Option<ChannelSnapshot>,
+        new: &ChannelSnapshot,
+    ) -> Result<(), crate::Error> {
+        let mut e = CreateEmbed::default();
+        e.title("Channel Updated")
+            .color(YELLOW)
+            .description(new.id.get_interactive())
+            .timestamp(Utc::now())
+            .set_footer(footer(format!("Channel ID: {}", new.id)));
+
+        let Some(old) = old else {
+            e.field("Changes", "*Unknown, the channel was not cached*", false);
+            let record = LogRecord::new(LogKind::ChannelUpdate)
+                .channel(new.id)
+                .change(None::<u64>, new.record_value());
+            post_embed(data, record, e)?;
+
+            return Ok(());
+        };
+
+        if old.name != new.name {
+            e.field("Name", format!("{} -> {}", old.name, new.name), false);
+        }
+
+        if old.parent_id != new.parent_id {
+            e.field(
+                "Category",
+                format!(
+                    "{} -> {}",
+                    describe_parent(old.parent_id),
+                    describe_parent(new.parent_id)
+                ),
+                false,
+            );
+        }
+
+        if old.topic != new.topic {
+            let topic = |x: &Option<String>| {
+                x.clone()
+                    .filter(|x| !x.is_empty())
+                    .unwrap_or("*None*".to_string())
+            };
+            e.field("Old Topic", truncate(&topic(&old.topic)), false)
+                .field("New Topic", truncate(&topic(&new.topic)), false);
+        }
+
+        if old.overwrites != new.overwrites {
+            e.field(
+                "Permission Overwrites",
+                overwrite_diff(&old.overwrites, &new.overwrites),
+                false,
+            );
+        }
+
+        let record = LogRecord::new(LogKind::ChannelUpdate)
+            .channel(new.id)
+            .change(old.record_value(), new.record_value());
+
+        post_embed(data, record, e)?;
+
+        Ok(())
+    }
+
+    fn execute_channel_deleted_log(
+        data: &Data,
+        channel: &ChannelSnapshot,
+    ) -> Result<(), crate::Error> {
+        let mut e = CreateEmbed::default();
+        e.title("Channel Deleted")
+            .color(RED)
+            .description(format!("#{}", channel.name))
+            .timestamp(Utc::now())
+            .field("Type", &channel.kind, true)
+            .field("Category", describe_parent(channel.parent_id), true)
+            .set_footer(footer(format!("Channel ID: {}", channel.id)));
+
+        let record = LogRecord::new(LogKind::ChannelDelete)
+            .channel(channel.id)
+            .change(channel.record_value(), None::<u64>);
+
+        post_embed(data, record, e)?;
+
+        Self::warn_if_referenced(data, channel.id.0, &format!("Channel #{}", channel.name))
+    }
+
+    fn execute_role_created_log(data: &Data, role: &Role) -> Result<(), crate::Error> {
+        let mut e = CreateEmbed::default();
+        e.title("Role Created")
+            .color(GREEN)
+            .description(role.id.get_interactive())
+            .timestamp(Utc::now())
+            .field("Name", &role.name, true)
+            .field("Color", format!("#{}", role.colour.hex()), true)
+            .field("Permissions", permission_list(role.permissions), false)
+            .set_footer(footer(format!("Role ID: {}", role.id)));
+
+        let record = LogRecord::new(LogKind::RoleCreate)
+            .role(role.id)
+            .change(None::<u64>, role_value(role));
+
+        post_embed(data, record, e)?;
+
+        Ok(())
+    }
+
+    fn execute_role_updated_log(
+        data: &Data,
+        old: &Option<Role>,
+        new: &Role,
+    ) -> Result<(), crate::Error> {
+        let mut e = CreateEmbed::default();
+        e.title("Role Updated")
+            .color(YELLOW)
+            .description(new.id.get_interactive())
+            .timestamp(Utc::now())
+            .set_footer(footer(format!("Role ID: {}", new.id)));
+
+        let Some(old) = old else {
+            e.field("Changes", "*Unknown, the role was not cached*", false);
+            let record = LogRecord::new(LogKind::RoleUpdate)
+                .role(new.id)
+                .change(None::<u64>, role_value(new));
+            post_embed(data, record, e)?;
+
+            return Ok(());
+        };
+
+        if old.name != new.name {
+            e.field("Name", format!("{} -> {}", old.name, new.name), false);
+        }
+
+        if old.colour != new.colour {
+            e.field(
+                "Color",
+                format!("#{} -> #{}", old.colour.hex(), new.colour.hex()),
+                false,
+            );
+        }
+
+        if old.hoist != new.hoist {
+            e.field(
+                "Shown Separately",
+                format!("{} -> {}", old.hoist, new.hoist),
+                true,
+            );
+        }
+
+        if old.mentionable != new.mentionable {
+            e.field(
+                "Mentionable",
+                format!("{} -> {}", old.mentionable, new.mentionable),
+                true,
+            );
+        }
+
+        if old.permissions != new.permissions {
+            e.field(
+                "Permissions",
+                permission_diff(old.permissions, new.permissions),
+                false,
+            );
+        }
+
+        let record = LogRecord::new(LogKind::RoleUpdate)
+            .role(new.id)
+            .change(role_value(old), role_value(new));
+
+        post_embed(data, record, e)?;
+
+        Ok(())
+    }
+
+    fn execute_role_deleted_log(
+        data: &Data,
+        role_id: RoleId,
+        role: &Option<Role>,
+    ) -> Result<(), crate::Error> {
+        let name = match role {
+            Some(role) => format!("@{}", role.name),
+            None => "*Unknown, the role was not cached*".to_string(),
+        };
+
+        let mut e = CreateEmbed::default();
+        e.title("Role Deleted")
+            .color(RED)
+            .description(&name)
+            .timestamp(Utc::now())
+            .set_footer(footer(format!("Role ID: {}", role_id)));
+
+        if let Some(role) = role {
+            e.field("Permissions", permission_list(role.permissions), false);
+        }
+
+        let record = LogRecord::new(LogKind::RoleDelete)
+            .role(role_id)
+            .change(role.as_ref().map(role_value), None::<u64>);
+
+        post_embed(data, record, e)?;
+
+        Self::warn_if_referenced(data, role_id.0, &format!("Role {}", name))
+    }
+
+    /// A deleted id the bot still depends on breaks features silently, so this is always
+    /// posted to the major events channel and can't be routed away
+    fn warn_if_referenced(data: &Data, id: u64, what: &str) -> Result<(), crate::Error> {
+        let references = references(data, id)?;
+        if references.is_empty() {
+            return Ok(());
+        }
+
+        tracing::error!(
+            "{} ({}) was deleted but is still referenced by: {}",
+            what,
+            id,
+            references.join(", ")
+        );
+
+        let mut e = CreateEmbed::default();
+        e.title("⚠️ Deleted ID Still In Use ⚠️")
+            .color(RED)
+            .description(format!(
+                "{} was deleted, but the bot still depends on it. \
+                 Update the secrets / bot state below or these features will break",
+                what
+            ))
+            .timestamp(Utc::now())
+            .field("Referenced By", references.join("\n"), false)
+            .set_footer(footer(format!("ID: {}", id)));
+
+        data.log_dispatcher.enqueue(LogJob {
+            channel_id: channel_from_secret(&data.major_events_channel, "MAJOR_EVENTS_CHANNEL")?,
+            embed: e,
+            coalesce: None,
+        });
+
+        Ok(())
+    }
+}
+
+// Core functionality
+impl GuildEvent {
+    /// `None` when nothing the log shows changed, e.g. when only the position moved
+    pub fn channel_updated(old: &Option<Channel>, new: &Channel) -> Option<Self> {
+        let new = ChannelSnapshot::from_channel(new)?;
+        let old = old.as_ref().and_then(ChannelSnapshot::from_channel);
+
+        if let Some(old) = &old {
+            if old.name == new.name
+                && old.parent_id == new.parent_id
+                && old.topic == new.topic
+                && old.overwrites == new.overwrites
+            {
+                return None;
+            }
+        }
+
+        Some(Self::ChannelUpdated(old, new))
+    }
+
+    /// `None` when nothing the log shows changed, reordering roles updates every role below
+    pub fn role_updated(old: &Option<Role>, new: &Role) -> Option<Self> {
+        if let Some(old) = old {
+            if old.name == new.name
+                && old.colour == new.colour
+                && old.hoist == new.hoist
+                && old.mentionable == new.mentionable
+                && old.permissions == new.permissions
+            {
+                return None;
+            }
+        }
+
+        Some(Self::RoleUpdated(old.clone(), new.clone()))
+    }
+
+    pub fn post_to_log_channel(&self, data: &Data) -> Result<(), crate::Error> {
+        match self {
+            Self::ChannelCreated(channel) => Self::execute_channel_created_log(data, channel)?,
+            Self::ChannelUpdated(old, new) => Self::execute_channel_updated_log(data, old, new)?,
+            Self::ChannelDeleted(channel) => Self::execute_channel_deleted_log(data, channel)?,
+            Self::RoleCreated(role) => Self::execute_role_created_log(data, role)?,
+            Self::RoleUpdated(old, new) => Self::execute_role_updated_log(data, old, new)?,
+            Self::RoleDeleted(role_id, role) => {
+                Self::execute_role_deleted_log(data, *role_id, role)?
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Every secret and state key that points at `id`
+fn references(data: &Data, id: u64) -> Result<Vec<String>, crate::Error> {
+    let id_string = id.to_string();
+    let mut out = vec![];
+
+    let secrets = [
+        ("MINOR_EVENTS_CHANNEL", &data.minor_events_channel),
+        ("MAJOR_EVENTS_CHANNEL", &data.major_events_channel),
+        ("FOLLOWER_ROLE", &data.follower_role),
+        ("TRIGGERED_ROLE", &data.triggered_role),
+        ("NEEDS_TO_APPLY_ROLE", &data.needs_to_apply_role),
+        ("NEEDS_TO_APPLY_CHANNEL", &data.needs_to_apply_channel),
+    ];
+    for (name, value) in secrets {
+        if *value == id_string {
+            out.push(format!("`{}` secret", name));
+        }
+    }
+
+    if data.guild_apply_roles.contains(&id_string) {
+        out.push("`GUILD_APPLY_ROLES` secret".to_string());
+    }
+
+    if data
+        .t_ids
+        .iter()
+        .any(|(role, room)| *role == id_string || *room == id_string)
+    {
+        out.push("`T_ROLES` / `T_ROOMS` secrets".to_string());
+    }
+
+    if Games::load(data)?.snowflakes().contains(&RoleId(id)) {
+        out.push("`games` state".to_string());
+    }
+
+    if TRooms::load(data)?.mentions(id) {
+        out.push("`t_rooms` state".to_string());
+    }
+
+    if LogRoutes::load(data)?.routes_to(id) {
+        out.push("`log_routes` state".to_string());
+    }
+
+    if GuildApply::load(data)?.snowflake_value_found(&id) {
+        out.push("`guild_apply` state".to_string());
+    }
+
+    if GameActivity::load(data)?.aliases.values().any(|x| *x == id) {
+        out.push("`game_activity` aliases".to_string());
+    }
+
+    let quarantine_role = Quarantine::load(data)?.settings.map(|x| x.role_id);
+    if quarantine_role == Some(id) {
+        out.push("`quarantine` state".to_string());
+    }
+
+    Ok(out)
+}
+
+fn permission_list(permissions: Permissions) -> String {
+    let names = permissions.get_permission_names();
+    match names.is_empty() {
+        true => "*None*".to_string(),
+        false => truncate(&names.join(", ")),
+    }
+}
+
+/// What structured log sinks see of the role, permissions as the raw bitfield
+fn role_value(role: &Role) -> serde_json::Value {
+    serde_json::json!({
+        "name": role.name,
+        "color": role.colour.0,
+        "hoist": role.hoist,
+        "mentionable": role.mentionable,
+        "permissions": role.permissions.bits().to_string(),
+    })
+}
+
+fn permission_diff(old: Permissions, new: Permissions) -> String {
+    let mut lines = vec![];
+
+    for name in (new - old).get_permission_names() {
+        lines.push(format!("+ {}", name));
+    }
+    for name in (old - new).get_permission_names() {
+        lines.push(format!("- {}", name));
+    }
+
+    code_block(&lines)
+}
+
+/// Per role / member, which allowed and denied permissions were added or removed
+fn overwrite_diff(old: &[PermissionOverwrite], new: &[PermissionOverwrite]) -> String {
+    let mut targets: Vec<PermissionOverwriteType> = vec![];
+    for overwrite in old.iter().chain(new.iter()) {
+        if !targets.contains(&overwrite.kind) {
+            targets.push(overwrite.kind);
+        }
+    }
+
+    let find = |overwrites: &[PermissionOverwrite], target| {
+        overwrites
+            .iter()
+            .find(|x| x.kind == target)
+            .map(|x| (x.allow, x.deny))
+            .unwrap_or((Permissions::empty(), Permissions::empty()))
+    };
+
+    let mut lines = vec![];
+    for target in targets {
+        let (old_allow, old_deny) = find(old, target);
+        let (new_allow, new_deny) = find(new, target);
+
+        let mut changes = vec![];
+        let mut push = |sign: &str, state: &str, permissions: Permissions| {
+            for name in permissions.get_permission_names() {
+                changes.push(format!("{} {} {}", sign, state, name));
+            }
+        };
+        push("+", "allow", new_allow - old_allow);
+        push("-", "allow", old_allow - new_allow);
+        push("+", "deny", new_deny - old_deny);
+        push("-", "deny", old_deny - new_deny);
+
+        if changes.is_empty() {
+            continue;
+        }
+
+        let target = match target {
+            PermissionOverwriteType::Member(user_id) => format!("Member {}", user_id),
+            PermissionOverwriteType::Role(role_id) => format!("Role {}", role_id),
+            _ => "Unknown".to_string(),
+        };
+
+        lines.push(format!("# {}", target));
+        lines.extend(changes);
+    }
+
+    code_block(&lines)
+}
+
+/// Renders lines in a `diff` code block, mentions don't resolve inside it so ids are shown
+fn code_block(lines: &[String]) -> String {
+    if lines.is_empty() {
+        return "*No changes*".to_string();
+    }
+
+    let budget = FIELD_LIMIT - "```diff\n\n```".len();
+    format!("```diff\n{}\n```", truncate_to(&lines.join("\n"), budget))
+}
@@ -0,0 +1,128 @@
+use poise::serenity_prelude::{GuildId, Http, InviteCreateEvent, InviteDeleteEvent, UserId};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Use counts of the guild's invites, Discord doesn't say which invite a new member used
+#[derive(Default)]
+pub struct InviteCache(Mutex<HashMap<String, CachedInvite>>);
+
+#[derive(Clone, Debug)]
+struct CachedInvite {
+    inviter: Option<UserId>,
+    uses: u64,
+    /// 0 means unlimited
+    max_uses: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct UsedInvite {
+    pub code: String,
+    pub inviter: Option<UserId>,
+}
+
+impl InviteCache {
+    /// Replaces the cache with the guild's current invites
+    pub async fn refresh(&self, http: &Http, guild_id: GuildId) -> Result<(), crate::Error> {
+        let invites = Self::fetch(http, guild_id).await?;
+        *self.0.lock().expect("InviteCache mutex poisoned") = invites;
+
+        Ok(())
+    }
+
+    pub fn created(&self, event: &InviteCreateEvent) {
+        let mut cache = self.0.lock().expect("InviteCache mutex poisoned");
+        cache.insert(
+            event.code.clone(),
+            CachedInvite {
+                inviter: event.inviter.as_ref().map(|x| x.id),
+                uses: 0,
+                max_uses: event.max_uses,
+            },
+        );
+    }
+
+    pub fn deleted(&self, event: &InviteDeleteEvent) {
+        let mut cache = self.0.lock().expect("InviteCache mutex poisoned");
+
+        // An invite hitting its last use is deleted around the same time the member joins,
+        // it is kept so the join can still be matched to it
+        let used_up = cache
+            .get(&event.code)
+            .map(|x| x.max_uses > 0 && x.uses + 1 >= x.max_uses)
+            .unwrap_or(false);
+
+        if !used_up {
+            cache.remove(&event.code);
+        }
+    }
+
+    /// Compares the guild's current invites against the cache to find the one a new member used
+    pub async fn find_used(&self, http: &Http, guild_id: GuildId) -> Option<UsedInvite> {
+        let fresh = match Self::fetch(http, guild_id).await {
+            Ok(fresh) => fresh,
+            Err(e) => {
+                // Most likely the bot is missing the MANAGE_GUILD permission
+                tracing::warn!("Could not fetch invites: {}", e);
+                return None;
+            }
+        };
+
+        let mut cache = self.0.lock().expect("InviteCache mutex poisoned");
+
+        let increased: Vec<(&String, &CachedInvite)> = fresh
+            .iter()
+            .filter(|(code, invite)| {
+                let before = cache.get(*code).map(|x| x.uses).unwrap_or(0);
+                invite.uses > before
+            })
+            .collect();
+
+        let used = match increased.as_slice() {
+            [(code, invite)] => Some(UsedInvite {
+                code: code.to_string(),
+                inviter: invite.inviter,
+            }),
+            // No use count went up, a used up invite is gone from the list instead
+            [] => {
+                let gone: Vec<(&String, &CachedInvite)> = cache
+                    .iter()
+                    .filter(|(code, _)| !fresh.contains_key(*code))
+                    .collect();
+
+                match gone.as_slice() {
+                    [(code, invite)] => Some(UsedInvite {
+                        code: code.to_string(),
+                        inviter: invite.inviter,
+                    }),
+                    _ => None,
+                }
+            }
+            // Several members joined at once, can't tell who used which
+            _ => None,
+        };
+
+        *cache = fresh;
+
+        used
+    }
+
+    async fn fetch(
+        http: &Http,
+        guild_id: GuildId,
+    ) -> Result<HashMap<String, CachedInvite>, crate::Error> {
+        let invites = guild_id.invites(http).await?;
+
+        Ok(invites
+            .into_iter()
+            .map(|x| {
+                let invite = CachedInvite {
+                    inviter: x.inviter.map(|x| x.id),
+                    uses: x.uses,
+                    max_uses: x.max_uses,
+                };
+
+                (x.code, invite)
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,345 @@
+use chrono::Utc;
+use poise::serenity_prelude::{
+    self as serenity,
+    colours::branding::{RED, YELLOW},
+    ChannelId, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, Message, MessageId,
+    MessageUpdateEvent, UserId,
+};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use crate::{
+    extensions::InteractiveSnowflakeExt,
+    log_channel::{post_embed, sinks::LogRecord},
+    state::{log_routes::LogKind, Data},
+    utils::{
+        discord_cdn::get_avatar_url,
+        embed::{truncate, truncate_to, FIELD_LIMIT},
+    },
+};
+
+/// How many messages are remembered, the oldest one is forgotten first
+const MESSAGE_CACHE_SIZE: usize = 5000;
+
+#[derive(Clone, Debug)]
+pub struct CachedMessage {
+    pub channel_id: ChannelId,
+    pub author_id: UserId,
+    pub author_name: String,
+    pub author_avatar: String,
+    pub content: String,
+    pub attachments: Vec<String>,
+}
+
+impl From<&Message> for CachedMessage {
+    fn from(message: &Message) -> Self {
+        Self {
+            channel_id: message.channel_id,
+            author_id: message.author.id,
+            author_name: message.author.name.clone(),
+            author_avatar: get_avatar_url(&message.author),
+            content: message.content.clone(),
+            attachments: message
+                .attachments
+                .iter()
+                .map(|x| x.filename.clone())
+                .collect(),
+        }
+    }
+}
+
+/// Guild messages seen since startup, Discord does not send the content of edited or deleted messages
+#[derive(Default)]
+pub struct MessageCache(Mutex<MessageCacheInner>);
+
+#[derive(Default)]
+struct MessageCacheInner {
+    messages: HashMap<MessageId, CachedMessage>,
+    order: VecDeque<MessageId>,
+}
+
+impl MessageCache {
+    pub fn insert(&self, message_id: MessageId, message: CachedMessage) {
+        let mut cache = self.0.lock().expect("MessageCache mutex poisoned");
+
+        if cache.messages.insert(message_id, message).is_none() {
+            cache.order.push_back(message_id);
+        }
+
+        while cache.order.len() > MESSAGE_CACHE_SIZE {
+            if let Some(oldest) = cache.order.pop_front() {
+                cache.messages.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn get(&self, message_id: &MessageId) -> Option<CachedMessage> {
+        let cache = self.0.lock().expect("MessageCache mutex poisoned");
+        cache.messages.get(message_id).cloned()
+    }
+
+    pub fn remove(&self, message_id: &MessageId) -> Option<CachedMessage> {
+        let mut cache = self.0.lock().expect("MessageCache mutex poisoned");
+        let message = cache.messages.remove(message_id)?;
+        cache.order.retain(|x| x != message_id);
+
+        Some(message)
+    }
+}
+
+pub enum MessageEvent {
+    /// The message before the edit is only known if it was cached
+    MessageEdited(MessageId, Option<CachedMessage>, CachedMessage),
+    MessageDeleted(MessageId, ChannelId, Option<CachedMessage>),
+    Unknown,
+}
+
+// Log channel functionality
+impl MessageEvent {
+    fn execute_message_edited_log(
+        data: &Data,
+        message_id: MessageId,
+        before: &Option<CachedMessage>,
+        after: &CachedMessage,
+    ) -> Result<(), crate::Error> {
+        let mut e = CreateEmbed::default();
+        let mut author = CreateEmbedAuthor::default();
+        author.icon_url(&after.author_avatar);
+        author.name(&after.author_name);
+
+        let mut footer = CreateEmbedFooter::default();
+        footer.text(format!(
+            "User ID: {} | Message ID: {}",
+            after.author_id, message_id
+        ));
+
+        e.title("Message Edited")
+            .color(YELLOW)
+            .description(format!("Channel: {}", after.channel_id.get_interactive()))
+            .timestamp(Utc::now())
+            .set_author(author)
+            .field("Display Name", after.author_id.get_interactive(), false);
+
+        match before {
+            Some(before) => e.field("Changes", diff(&before.content, &after.content), false),
+            None => e
+                .field(
+                    "Before",
+                    "*Unknown, the message was sent before the bot started*",
+                    false,
+                )
+                .field("After", field_value(&after.content), false),
+        };
+        e.set_footer(footer);
+
+        let record = LogRecord::new(LogKind::MessageEdit)
+            .user(after.author_id)
+            .channel(after.channel_id)
+            .message(message_id)
+            .change(before.as_ref().map(|x| &x.content), &after.content);
+
+        post_embed(data, record, e)?;
+
+        Ok(())
+    }
+
+    fn execute_message_deleted_log(
+        data: &Data,
+        message_id: MessageId,
+        channel_id: ChannelId,
+        message: &Option<CachedMessage>,
+    ) -> Result<(), crate::Error> {
+        let mut e = CreateEmbed::default();
+        e.title("Message Deleted")
+            .color(RED)
+            .description(format!("Channel: {}", channel_id.get_interactive()))
+            .timestamp(Utc::now());
+
+        match message {
+            Some(message) => {
+                let mut author = CreateEmbedAuthor::default();
+                author.icon_url(&message.author_avatar);
+                author.name(&message.author_name);
+
+                let mut footer = CreateEmbedFooter::default();
+                footer.text(format!(
+                    "User ID: {} | Message ID: {}",
+                    message.author_id, message_id
+                ));
+
+                let attachments = match message.attachments.is_empty() {
+                    true => "None".to_string(),
+                    false => message.attachments.join("\n"),
+                };
+
+                e.set_author(author)
+                    .field("Display Name", message.author_id.get_interactive(), false)
+                    .field("Content", field_value(&message.content), false)
+                    .field("Attachments", field_value(&attachments), false)
+                    .set_footer(footer)
+            }
+            None => {
+                let mut footer = CreateEmbedFooter::default();
+                footer.text(format!("Message ID: {}", message_id));
+
+                e.field(
+                    "Content",
+                    "*Unknown, the message was sent before the bot started*",
+                    false,
+                )
+                .set_footer(footer)
+            }
+        };
+
+        let mut record = LogRecord::new(LogKind::MessageDelete)
+            .channel(channel_id)
+            .message(message_id);
+        if let Some(message) = message {
+            record = record.user(message.author_id).change(
+                serde_json::json!({
+                    "content": message.content,
+                    "attachments": message.attachments,
+                }),
+                None::<u64>,
+            );
+        }
+
+        post_embed(data, record, e)?;
+
+        Ok(())
+    }
+}
+
+// Core functionality
+impl MessageEvent {
+    /// Compares an edit against the cached message and refreshes the cache with the new content
+    pub fn edited(data: &Data, event: &MessageUpdateEvent) -> Self {
+        let Some(content) = event.content.clone() else {
+            // Embed / pin updates carry no content
+            return Self::Unknown;
+        };
+
+        let before = data.message_cache.get(&event.id);
+        let after = match &before {
+            Some(before) => {
+                let mut after = before.clone();
+                after.content = content;
+                if let Some(attachments) = &event.attachments {
+                    after.attachments = attachments.iter().map(|x| x.filename.clone()).collect();
+                }
+                after
+            }
+            None => {
+                // Same messages as the ones cached on arrival, guild messages from members
+                let (Some(_), Some(author)) = (event.guild_id, &event.author) else {
+                    return Self::Unknown;
+                };
+                if author.bot {
+                    return Self::Unknown;
+                }
+
+                CachedMessage {
+                    channel_id: event.channel_id,
+                    author_id: author.id,
+                    author_name: author.name.clone(),
+                    author_avatar: get_avatar_url(author),
+                    content,
+                    attachments: event
+                        .attachments
+                        .iter()
+                        .flatten()
+                        .map(|x| x.filename.clone())
+                        .collect(),
+                }
+            }
+        };
+        data.message_cache.insert(event.id, after.clone());
+
+        if before.as_ref().is_some_and(|x| x.content == after.content) {
+            return Self::Unknown;
+        }
+
+        Self::MessageEdited(event.id, before, after)
+    }
+
+    pub fn deleted(data: &Data, channel_id: ChannelId, message_id: MessageId) -> Self {
+        Self::MessageDeleted(
+            message_id,
+            channel_id,
+            data.message_cache.remove(&message_id),
+        )
+    }
+
+    pub async fn post_to_log_channel(
+        &self,
+        _ctx: &serenity::Context,
+        data: &Data,
+    ) -> Result<(), crate::Error> {
+        match self {
+            Self::MessageEdited(message_id, before, after) => {
+                Self::execute_message_edited_log(data, *message_id, before, after)?;
+            }
+            Self::MessageDeleted(message_id, channel_id, message) => {
+                // Don't log the bot cleaning up its own log channels
+                if Self::is_log_channel(data, channel_id) {
+                    return Ok(());
+                }
+
+                Self::execute_message_deleted_log(data, *message_id, *channel_id, message)?;
+            }
+            Self::Unknown => (),
+        }
+
+        Ok(())
+    }
+
+    fn is_log_channel(data: &Data, channel_id: &ChannelId) -> bool {
+        let channel_id = channel_id.to_string();
+        channel_id == data.minor_events_channel || channel_id == data.major_events_channel
+    }
+}
+
+/// Line based diff of two message contents, rendered as a `diff` code block
+fn diff(before: &str, after: &str) -> String {
+    let before: Vec<&str> = before.lines().collect();
+    let after: Vec<&str> = after.lines().collect();
+
+    // Longest common subsequence table, messages are at most a few thousand characters
+    let mut lcs = vec![vec![0usize; after.len() + 1]; before.len() + 1];
+    for i in (0..before.len()).rev() {
+        for j in (0..after.len()).rev() {
+            lcs[i][j] = match before[i] == after[j] {
+                true => lcs[i + 1][j + 1] + 1,
+                false => lcs[i + 1][j].max(lcs[i][j + 1]),
+            };
+        }
+    }
+
+    let mut lines = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < before.len() || j < after.len() {
+        if i < before.len() && j < after.len() && before[i] == after[j] {
+            lines.push(format!("  {}", before[i]));
+            i += 1;
+            j += 1;
+        } else if j < after.len() && (i == before.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            lines.push(format!("+ {}", after[j]));
+            j += 1;
+        } else {
+            lines.push(format!("- {}", before[i]));
+            i += 1;
+        }
+    }
+
+    let lines = lines.join("\n").replace("```", "`\u{200b}``");
+    let budget = FIELD_LIMIT - "```diff\n\n```".len();
+
+    format!("```diff\n{}\n```", truncate_to(&lines, budget))
+}
+
+fn field_value(value: &str) -> String {
+    match value.is_empty() {
+        true => "*Empty*".to_string(),
+        false => truncate(value),
+    }
+}
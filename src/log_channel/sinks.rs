@@ -0,0 +1,156 @@
+use crate::state::{
+    log_routes::LogKind,
+    sink_filters::{SinkFilters, SinkKind},
+    Data,
+};
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+pub mod jsonl;
+pub mod webhook;
+
+/// Bump whenever a field of `LogRecord` is removed or changes meaning, adding fields is fine
+const SCHEMA_VERSION: u32 = 1;
+
+/// Records waiting to be written per sink, anything past this is dropped instead of piling up
+const QUEUE_SIZE: usize = 1000;
+
+/// Structured form of a log event, written to every enabled sink.
+///
+/// Every field is always present, unused ids and values are `null`.
+/// Ids are strings, they don't fit into a JSON number without losing precision
+#[derive(Serialize, Clone, Debug)]
+pub struct LogRecord {
+    pub schema_version: u32,
+    pub kind: LogKind,
+    /// Unix timestamp in seconds
+    pub timestamp: i64,
+    pub user_id: Option<String>,
+    pub channel_id: Option<String>,
+    pub role_id: Option<String>,
+    pub message_id: Option<String>,
+    /// Moderator responsible for the change, when the audit log tells
+    pub actor_id: Option<String>,
+    pub before: Value,
+    pub after: Value,
+}
+
+impl LogRecord {
+    pub fn new(kind: LogKind) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            kind,
+            timestamp: Utc::now().timestamp(),
+            user_id: None,
+            channel_id: None,
+            role_id: None,
+            message_id: None,
+            actor_id: None,
+            before: Value::Null,
+            after: Value::Null,
+        }
+    }
+
+    pub fn user<T: Into<u64>>(mut self, id: T) -> Self {
+        self.user_id = Some(id.into().to_string());
+        self
+    }
+
+    pub fn channel<T: Into<u64>>(mut self, id: T) -> Self {
+        self.channel_id = Some(id.into().to_string());
+        self
+    }
+
+    pub fn role<T: Into<u64>>(mut self, id: T) -> Self {
+        self.role_id = Some(id.into().to_string());
+        self
+    }
+
+    pub fn message<T: Into<u64>>(mut self, id: T) -> Self {
+        self.message_id = Some(id.into().to_string());
+        self
+    }
+
+    pub fn actor<T: Into<u64>>(mut self, id: Option<T>) -> Self {
+        self.actor_id = id.map(|x| x.into().to_string());
+        self
+    }
+
+    pub fn change<B: Serialize, A: Serialize>(mut self, before: B, after: A) -> Self {
+        self.before = serde_json::to_value(before).unwrap_or(Value::Null);
+        self.after = serde_json::to_value(after).unwrap_or(Value::Null);
+        self
+    }
+}
+
+/// An output for structured log events
+#[async_trait]
+pub trait EventSink: Send + Sync {
+    fn kind(&self) -> SinkKind;
+
+    async fn write(&mut self, record: &LogRecord) -> Result<(), anyhow::Error>;
+}
+
+/// Hands log records to the configured sinks.
+///
+/// Each sink has its own queue and background task, so a slow webhook never holds up the others
+pub struct EventSinks {
+    senders: Vec<(SinkKind, mpsc::Sender<LogRecord>)>,
+    configured: Vec<SinkKind>,
+}
+
+impl EventSinks {
+    pub fn start(sinks: Vec<Box<dyn EventSink>>) -> Self {
+        let configured = sinks.iter().map(|x| x.kind()).collect();
+        let senders = sinks
+            .into_iter()
+            .map(|sink| {
+                let kind = sink.kind();
+                let (sender, receiver) = mpsc::channel(QUEUE_SIZE);
+                tokio::spawn(run(sink, receiver));
+                (kind, sender)
+            })
+            .collect();
+
+        Self {
+            senders,
+            configured,
+        }
+    }
+
+    /// Queues the record for every configured sink that has its kind enabled
+    pub fn emit(&self, data: &Data, record: LogRecord) -> Result<(), crate::Error> {
+        if self.configured.is_empty() {
+            return Ok(());
+        }
+
+        let filters = SinkFilters::load(data)?;
+        let targets = self
+            .senders
+            .iter()
+            .filter(|(kind, _)| filters.is_enabled(*kind, record.kind));
+
+        for (kind, sender) in targets {
+            if let Err(e) = sender.try_send(record.clone()) {
+                tracing::warn!("Log record dropped for the {:?} sink: {}", kind, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn configured(&self) -> &[SinkKind] {
+        &self.configured
+    }
+}
+
+async fn run(mut sink: Box<dyn EventSink>, mut receiver: mpsc::Receiver<LogRecord>) {
+    while let Some(record) = receiver.recv().await {
+        if let Err(e) = sink.write(&record).await {
+            tracing::error!("{:?} sink failed to write a record: {}", sink.kind(), e);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use super::{EventSink, LogRecord};
+use crate::state::sink_filters::SinkKind;
+use async_trait::async_trait;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// The current file is rotated once it grows past this
+const MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Rotated files kept next to the current one, `events.1.jsonl` being the newest
+const ROTATED_FILES: usize = 5;
+
+const FILE_NAME: &str = "events";
+
+/// Appends one JSON object per line to `<dir>/events.jsonl`
+pub struct JsonlSink {
+    dir: PathBuf,
+    file: Option<File>,
+    size: u64,
+}
+
+impl JsonlSink {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            file: None,
+            size: 0,
+        }
+    }
+
+    fn path(&self, index: usize) -> PathBuf {
+        match index {
+            0 => self.dir.join(format!("{}.jsonl", FILE_NAME)),
+            _ => self.dir.join(format!("{}.{}.jsonl", FILE_NAME, index)),
+        }
+    }
+
+    fn open(&mut self) -> Result<&mut File, anyhow::Error> {
+        if self.file.is_none() {
+            fs::create_dir_all(&self.dir)?;
+
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.path(0))?;
+            self.size = file.metadata()?.len();
+            self.file = Some(file);
+        }
+
+        Ok(self.file.as_mut().expect("JsonlSink file was just opened"))
+    }
+
+    /// Shifts every file up by one, the oldest one is overwritten
+    fn rotate(&mut self) -> Result<(), anyhow::Error> {
+        self.file = None;
+
+        for index in (0..ROTATED_FILES).rev() {
+            let from = self.path(index);
+            if from.exists() {
+                fs::rename(from, self.path(index + 1))?;
+            }
+        }
+
+        self.size = 0;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EventSink for JsonlSink {
+    fn kind(&self) -> SinkKind {
+        SinkKind::Jsonl
+    }
+
+    // Records are small, a blocking append is cheaper than handing it to another thread
+    async fn write(&mut self, record: &LogRecord) -> Result<(), anyhow::Error> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+
+        if self.size > 0 && self.size + line.len() as u64 > MAX_FILE_BYTES {
+            self.rotate()?;
+        }
+
+        self.open()?.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+
+        Ok(())
+    }
+}
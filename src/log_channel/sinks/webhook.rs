@@ -0,0 +1,47 @@
+use super::{EventSink, LogRecord};
+use crate::state::sink_filters::SinkKind;
+use async_trait::async_trait;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// POSTs every record as a JSON body to a fixed url
+pub struct WebhookSink {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl WebhookSink {
+    pub fn new(url: String) -> Result<Self, anyhow::Error> {
+        // Caught at startup instead of failing every post
+        let scheme = reqwest::Url::parse(&url)?.scheme().to_string();
+        if scheme != "http" && scheme != "https" {
+            return Err(anyhow::anyhow!(
+                "Event webhook URL must be http or https, not '{}'",
+                scheme
+            ));
+        }
+
+        let client = reqwest::Client::builder().timeout(TIMEOUT).build()?;
+
+        Ok(Self { client, url })
+    }
+}
+
+#[async_trait]
+impl EventSink for WebhookSink {
+    fn kind(&self) -> SinkKind {
+        SinkKind::Webhook
+    }
+
+    async fn write(&mut self, record: &LogRecord) -> Result<(), anyhow::Error> {
+        self.client
+            .post(&self.url)
+            .json(record)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
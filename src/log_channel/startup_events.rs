@@ -0,0 +1,71 @@
+use chrono::Utc;
+use poise::serenity_prelude::{
+    self as serenity, colours::branding::YELLOW, ChannelId, CreateEmbed,
+};
+
+use crate::error::channel_from_secret;
+use crate::log_channel::dispatcher::LogJob;
+use crate::state::{
+    t_rooms::{Room, RoomChanges},
+    Data,
+};
+
+pub enum StartupEvent {
+    TRoomsReconciled(RoomChanges),
+}
+
+// Log channel functionality
+impl StartupEvent {
+    fn execute_t_rooms_reconciled_log(
+        data: &Data,
+        changes: &RoomChanges,
+    ) -> Result<(), crate::Error> {
+        let target_channel = Self::get_major_event_channel(data)?;
+
+        let describe = |rooms: &Vec<Room>| -> String {
+            let rooms: Vec<String> = rooms.iter().map(|x| x.describe()).collect();
+
+            match rooms.is_empty() {
+                true => "None".to_string(),
+                false => rooms.join("\n"),
+            }
+        };
+
+        let mut e = CreateEmbed::default();
+        e.title("Triggered Rooms Reconciled")
+            .color(YELLOW)
+            .description("Stored rooms were updated to match T_ROLES / T_ROOMS")
+            .timestamp(Utc::now())
+            .field("Added", describe(&changes.added), false)
+            .field("Removed", describe(&changes.removed), false)
+            .field("Role Changed", describe(&changes.role_changed), false)
+            .field("Duplicates Dropped", changes.duplicates_dropped, true);
+
+        // Startup notices are not routable, they always go to the major events channel
+        data.log_dispatcher.enqueue(LogJob {
+            channel_id: target_channel,
+            embed: e,
+            coalesce: None,
+        });
+
+        Ok(())
+    }
+
+    pub async fn post_to_log_channel(
+        &self,
+        _ctx: &serenity::Context,
+        data: &Data,
+    ) -> Result<(), crate::Error> {
+        match self {
+            Self::TRoomsReconciled(changes) => {
+                Self::execute_t_rooms_reconciled_log(data, changes)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_major_event_channel(data: &Data) -> Result<ChannelId, crate::Error> {
+        channel_from_secret(&data.major_events_channel, "MAJOR_EVENTS_CHANNEL")
+    }
+}
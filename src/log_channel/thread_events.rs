@@ -0,0 +1,371 @@
+use chrono::Utc;
+use poise::serenity_prelude::{
+    self as serenity,
+    colours::branding::{GREEN, RED, YELLOW},
+    ChannelId, CreateEmbed, GuildChannel, GuildId, PartialGuildChannel, ThreadMembersUpdateEvent,
+    UserId,
+};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::{
+    extensions::InteractiveSnowflakeExt,
+    log_channel::{
+        attribution::{Attribution, AuditedChange},
+        post_embed,
+        sinks::LogRecord,
+    },
+    state::{log_routes::LogKind, Data},
+    utils::embed::{describe_parent, footer},
+};
+
+/// The parts of a thread the log cares about
+#[derive(Clone, Debug)]
+pub struct ThreadSnapshot {
+    pub id: ChannelId,
+    pub name: String,
+    pub parent_id: Option<ChannelId>,
+    /// Whoever started the thread
+    pub owner_id: Option<UserId>,
+    pub archived: bool,
+    /// Archived by Discord after `auto_archive_duration` minutes without activity
+    pub auto_archived: bool,
+    pub locked: bool,
+}
+
+impl From<&GuildChannel> for ThreadSnapshot {
+    fn from(thread: &GuildChannel) -> Self {
+        let metadata = thread.thread_metadata.as_ref();
+
+        Self {
+            id: thread.id,
+            name: thread.name.clone(),
+            parent_id: thread.parent_id,
+            owner_id: thread.owner_id,
+            archived: metadata.map(|x| x.archived).unwrap_or(false),
+            auto_archived: is_auto_archived(thread),
+            locked: metadata.map(|x| x.locked).unwrap_or(false),
+        }
+    }
+}
+
+impl ThreadSnapshot {
+    fn record_value(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "parent_id": self.parent_id.map(|x| x.0.to_string()),
+            "owner_id": self.owner_id.map(|x| x.0.to_string()),
+            "archived": self.archived,
+            "locked": self.locked,
+        })
+    }
+}
+
+/// Last known state of every thread, `ThreadUpdate` doesn't carry the old one
+#[derive(Default)]
+pub struct ThreadCache(Mutex<HashMap<ChannelId, ThreadSnapshot>>);
+
+impl ThreadCache {
+    /// Stores the thread, handing back what was known about it before
+    pub fn insert(&self, thread: ThreadSnapshot) -> Option<ThreadSnapshot> {
+        let mut cache = self.0.lock().expect("ThreadCache mutex poisoned");
+        cache.insert(thread.id, thread)
+    }
+
+    pub fn extend<'a, I>(&self, threads: I)
+    where
+        I: IntoIterator<Item = &'a GuildChannel>,
+    {
+        let mut cache = self.0.lock().expect("ThreadCache mutex poisoned");
+        cache.extend(threads.into_iter().map(|x| (x.id, x.into())));
+    }
+
+    pub fn remove(&self, id: ChannelId) -> Option<ThreadSnapshot> {
+        let mut cache = self.0.lock().expect("ThreadCache mutex poisoned");
+        cache.remove(&id)
+    }
+}
+
+pub enum ThreadEvent {
+    Created(ThreadSnapshot),
+    /// Only posted when the thread was archived, locked or the reverse
+    Updated(GuildId, Option<ThreadSnapshot>, ThreadSnapshot),
+    /// Parent channel, and the thread if it was cached
+    Deleted(GuildId, ChannelId, ChannelId, Option<ThreadSnapshot>),
+    MembersChanged(ChannelId, Vec<UserId>, Vec<UserId>),
+}
+
+impl ThreadEvent {
+    /// `None` for a thread that was already known, Discord also sends `ThreadCreate`
+    /// when the bot gains access to an existing thread
+    pub fn created(data: &Data, thread: &GuildChannel) -> Option<Self> {
+        let thread = ThreadSnapshot::from(thread);
+        let known = data.thread_cache.insert(thread.clone()).is_some();
+
+        (!known).then_some(Self::Created(thread))
+    }
+
+    pub fn updated(data: &Data, guild_id: GuildId, thread: &GuildChannel) -> Self {
+        let thread = ThreadSnapshot::from(thread);
+        let old = data.thread_cache.insert(thread.clone());
+
+        Self::Updated(guild_id, old, thread)
+    }
+
+    pub fn deleted(data: &Data, thread: &PartialGuildChannel) -> Self {
+        let old = data.thread_cache.remove(thread.id);
+
+        Self::Deleted(thread.guild_id, thread.id, thread.parent_id, old)
+    }
+
+    pub fn members_changed(event: &ThreadMembersUpdateEvent) -> Self {
+        let joined = event
+            .added_members
+            .iter()
+            .filter_map(|x| x.user_id)
+            .collect();
+
+        Self::MembersChanged(event.id, joined, event.removed_members_ids.clone())
+    }
+}
+
+// Log channel functionality
+impl ThreadEvent {
+    fn execute_thread_created_log(
+        data: &Data,
+        thread: &ThreadSnapshot,
+    ) -> Result<(), crate::Error> {
+        let mut e = CreateEmbed::default();
+        e.title("Thread Created")
+            .color(GREEN)
+            .description(thread.id.get_interactive())
+            .timestamp(Utc::now())
+            .field("Name", &thread.name, true)
+            .field("Channel", describe_parent(thread.parent_id), true)
+            .field(
+                "Created By",
+                thread
+                    .owner_id
+                    .map(|x| x.get_interactive())
+                    .unwrap_or("*Unknown*".to_string()),
+                true,
+            )
+            .set_footer(footer(format!("Thread ID: {}", thread.id)));
+
+        let record = LogRecord::new(LogKind::ThreadCreate)
+            .channel(thread.id)
+            .actor(thread.owner_id)
+            .change(None::<u64>, thread.record_value());
+
+        post_embed(data, record, e)?;
+
+        Ok(())
+    }
+
+    async fn execute_thread_updated_log(
+        ctx: &serenity::Context,
+        data: &Data,
+        guild_id: GuildId,
+        old: &ThreadSnapshot,
+        new: &ThreadSnapshot,
+    ) -> Result<(), crate::Error> {
+        let changes = [
+            (
+                old.archived != new.archived,
+                LogKind::ThreadArchive,
+                AuditedChange::ThreadArchive,
+                match new.archived {
+                    true => "Thread Archived",
+                    false => "Thread Unarchived",
+                },
+            ),
+            (
+                old.locked != new.locked,
+                LogKind::ThreadLock,
+                AuditedChange::ThreadLock,
+                match new.locked {
+                    true => "Thread Locked",
+                    false => "Thread Unlocked",
+                },
+            ),
+        ];
+
+        // Unarchiving and auto archiving leave no audit log entry to look up
+        let unaudited_archive = match (new.archived, new.auto_archived) {
+            (false, _) => Some("*Unknown, sending a message unarchives a thread*"),
+            (true, true) => Some("*Discord, the thread was inactive*"),
+            (true, false) => None,
+        };
+
+        for (_, kind, change, title) in changes.into_iter().filter(|x| x.0) {
+            let unaudited = match kind {
+                LogKind::ThreadArchive => unaudited_archive,
+                _ => None,
+            };
+
+            let actor = match unaudited {
+                Some(_) => None,
+                None => Attribution::lookup_channel(ctx, guild_id, change, new.id)
+                    .await
+                    .moderator(),
+            };
+            let changed_by = match unaudited {
+                Some(note) => note.to_string(),
+                None => describe_actor(actor),
+            };
+
+            let mut e = CreateEmbed::default();
+            e.title(title)
+                .color(YELLOW)
+                .description(new.id.get_interactive())
+                .timestamp(Utc::now())
+                .field("Name", &new.name, true)
+                .field("Channel", describe_parent(new.parent_id), true)
+                .field("Changed By", changed_by, true)
+                .set_footer(footer(format!("Thread ID: {}", new.id)));
+
+            let record = LogRecord::new(kind)
+                .channel(new.id)
+                .actor(actor)
+                .change(old.record_value(), new.record_value());
+
+            post_embed(data, record, e)?;
+        }
+
+        Ok(())
+    }
+
+    async fn execute_thread_deleted_log(
+        ctx: &serenity::Context,
+        data: &Data,
+        guild_id: GuildId,
+        thread_id: ChannelId,
+        parent_id: ChannelId,
+        thread: &Option<ThreadSnapshot>,
+    ) -> Result<(), crate::Error> {
+        let attribution =
+            Attribution::lookup_channel(ctx, guild_id, AuditedChange::ThreadDelete, thread_id)
+                .await;
+
+        let name = match thread {
+            Some(thread) => thread.name.clone(),
+            None => "*Unknown, the thread was not cached*".to_string(),
+        };
+
+        let mut e = CreateEmbed::default();
+        e.title("Thread Deleted")
+            .color(RED)
+            .description(&name)
+            .timestamp(Utc::now())
+            .field("Channel", parent_id.get_interactive(), true)
+            .field("Deleted By", describe_actor(attribution.moderator()), true)
+            .set_footer(footer(format!("Thread ID: {}", thread_id)));
+
+        let record = LogRecord::new(LogKind::ThreadDelete)
+            .channel(thread_id)
+            .actor(attribution.moderator())
+            .change(thread.as_ref().map(|x| x.record_value()), None::<u64>);
+
+        post_embed(data, record, e)?;
+
+        Ok(())
+    }
+
+    fn execute_thread_members_log(
+        data: &Data,
+        thread_id: ChannelId,
+        users: &[UserId],
+        joined: bool,
+    ) -> Result<(), crate::Error> {
+        if users.is_empty() {
+            return Ok(());
+        }
+
+        let (title, color, kind) = match joined {
+            true => ("Joined Thread", GREEN, LogKind::ThreadMemberJoin),
+            false => ("Left Thread", RED, LogKind::ThreadMemberLeave),
+        };
+
+        let mentions: Vec<String> = users.iter().map(|x| x.get_interactive()).collect();
+
+        let mut e = CreateEmbed::default();
+        e.title(title)
+            .color(color)
+            .description(thread_id.get_interactive())
+            .timestamp(Utc::now())
+            .field("Members", mentions.join(" "), false)
+            .set_footer(footer(format!("Thread ID: {}", thread_id)));
+
+        let ids: Vec<String> = users.iter().map(|x| x.0.to_string()).collect();
+        let (before, after) = match joined {
+            true => (None, Some(ids)),
+            false => (Some(ids), None),
+        };
+        let record = LogRecord::new(kind)
+            .channel(thread_id)
+            .change(before, after);
+
+        post_embed(data, record, e)?;
+
+        Ok(())
+    }
+
+    pub async fn post_to_log_channel(
+        &self,
+        ctx: &serenity::Context,
+        data: &Data,
+    ) -> Result<(), crate::Error> {
+        match self {
+            Self::Created(thread) => {
+                Self::execute_thread_created_log(data, thread)?;
+            }
+            Self::Updated(guild_id, Some(old), new) => {
+                Self::execute_thread_updated_log(ctx, data, *guild_id, old, new).await?;
+            }
+            // Nothing to compare against, the thread is cached from now on
+            Self::Updated(_, None, _) => (),
+            Self::Deleted(guild_id, thread_id, parent_id, thread) => {
+                Self::execute_thread_deleted_log(
+                    ctx, data, *guild_id, *thread_id, *parent_id, thread,
+                )
+                .await?;
+            }
+            Self::MembersChanged(thread_id, joined, left) => {
+                Self::execute_thread_members_log(data, *thread_id, joined, true)?;
+                Self::execute_thread_members_log(data, *thread_id, left, false)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Threads are mostly changed by regular members, so whoever the audit log names is shown
+fn describe_actor(actor: Option<UserId>) -> String {
+    actor
+        .map(|x| x.get_interactive())
+        .unwrap_or("*Unknown, no audit log entry found*".to_string())
+}
+
+/// Discord archives a thread once it has been inactive for its auto archive duration,
+/// a thread archived any sooner was archived by someone
+fn is_auto_archived(thread: &GuildChannel) -> bool {
+    let Some(metadata) = &thread.thread_metadata else {
+        return false;
+    };
+    let (true, Some(archived_at), Some(minutes)) = (
+        metadata.archived,
+        metadata.archive_timestamp,
+        metadata.auto_archive_duration,
+    ) else {
+        return false;
+    };
+
+    let last_activity = thread
+        .last_message_id
+        .map(|x| x.created_at())
+        .or(metadata.create_timestamp)
+        .unwrap_or(thread.id.created_at());
+
+    archived_at.unix_timestamp() - last_activity.unix_timestamp() >= minutes as i64 * 60
+}
@@ -1,41 +1,121 @@
 use chrono::Utc;
 use poise::serenity_prelude::{
     self as serenity,
-    colours::branding::{GREEN, RED, YELLOW},
-    ChannelId, CreateEmbedAuthor, CreateEmbedFooter, Member, Role, RoleId, UserId,
+    colours::branding::{FUCHSIA, GREEN, RED, YELLOW},
+    CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, GuildId, Member, RoleId, Timestamp, UserId,
 };
-use std::str::FromStr;
 
 use crate::{
     extensions::InteractiveSnowflakeExt,
-    state::Data,
+    log_channel::{
+        attribution::{Attribution, AuditedChange},
+        invite_tracking::UsedInvite,
+        post_embed,
+        sinks::LogRecord,
+    },
+    state::{
+        log_routes::LogKind,
+        member_history::{MemberHistory, MemberHistoryEvent},
+        member_snapshots::MemberSnapshot,
+        Data,
+    },
     utils::{discord_cdn::get_avatar_url, time::date_diff},
 };
 
+/// What is known about a member that just left, from the cache or else their last snapshot
+pub struct DepartedMember {
+    pub roles: Vec<RoleId>,
+    pub joined_at: Option<Timestamp>,
+    /// Unix timestamp in seconds
+    pub last_seen: Option<i64>,
+}
+
+impl DepartedMember {
+    pub fn new(member: Option<&Member>, snapshot: Option<MemberSnapshot>) -> Self {
+        let snapshot = snapshot.unwrap_or_default();
+
+        match member {
+            Some(member) => Self {
+                roles: member.roles.clone(),
+                joined_at: member.joined_at,
+                last_seen: snapshot.last_seen,
+            },
+            None => Self {
+                roles: snapshot.roles.into_iter().map(RoleId).collect(),
+                joined_at: snapshot
+                    .joined_at
+                    .and_then(|x| Timestamp::from_unix_timestamp(x).ok()),
+                last_seen: snapshot.last_seen,
+            },
+        }
+    }
+}
+
+#[allow(clippy::enum_variant_names)]
 pub enum UserEvent {
-    UserJoin(UserId),
-    UserLeave(UserId, Vec<Role>),
-    UserBan(UserId),
+    UserJoin(UserId, Option<UsedInvite>),
+    UserLeave(UserId, DepartedMember),
+    UserBan(GuildId, UserId),
     UserUnban(UserId),
-    UserChange(UserId, UserChangeType),
+    UserChange(GuildId, UserId, Vec<UserChangeType>),
 }
 
+#[allow(clippy::enum_variant_names)]
 pub enum UserChangeType {
     RolesChanged(RoleState),
     NickNameChanged(Option<String>, Option<String>),
-    Unknown,
+    /// Old and new end of the timeout
+    TimeoutChanged(Option<Timestamp>, Option<Timestamp>),
+    /// Old and new server avatar url
+    GuildAvatarChanged(Option<String>, Option<String>),
+    /// Start of the boost, `None` once it stopped
+    BoostChanged(Option<Timestamp>),
+    /// Whether the member still has to pass membership screening
+    PendingChanged(bool),
 }
 
 // Core functionality
 impl UserChangeType {
-    pub fn new(old_state: &Member, new_state: &Member) -> Self {
+    /// Every difference between the two states, a single update can carry several
+    pub fn new(old_state: &Member, new_state: &Member) -> Vec<Self> {
+        let mut changes = vec![];
+
         if old_state.nick != new_state.nick {
-            UserChangeType::NickNameChanged(old_state.nick.clone(), new_state.nick.clone())
-        } else if old_state.roles != new_state.roles {
-            UserChangeType::RolesChanged(Self::get_role_changes(old_state, new_state))
-        } else {
-            UserChangeType::Unknown
+            changes.push(UserChangeType::NickNameChanged(
+                old_state.nick.clone(),
+                new_state.nick.clone(),
+            ));
+        }
+
+        if old_state.roles != new_state.roles {
+            changes.push(UserChangeType::RolesChanged(Self::get_role_changes(
+                old_state, new_state,
+            )));
         }
+
+        if old_state.communication_disabled_until != new_state.communication_disabled_until {
+            changes.push(UserChangeType::TimeoutChanged(
+                old_state.communication_disabled_until,
+                new_state.communication_disabled_until,
+            ));
+        }
+
+        if old_state.avatar != new_state.avatar {
+            changes.push(UserChangeType::GuildAvatarChanged(
+                old_state.avatar_url(),
+                new_state.avatar_url(),
+            ));
+        }
+
+        if old_state.premium_since != new_state.premium_since {
+            changes.push(UserChangeType::BoostChanged(new_state.premium_since));
+        }
+
+        if old_state.pending != new_state.pending {
+            changes.push(UserChangeType::PendingChanged(new_state.pending));
+        }
+
+        changes
     }
 
     fn get_role_changes(old_state: &Member, new_state: &Member) -> RoleState {
@@ -74,33 +154,65 @@ impl UserEvent {
         ctx: &serenity::Context,
         data: &Data,
         user_id: UserId,
+        invite: &Option<UsedInvite>,
     ) -> Result<(), crate::Error> {
-        let target_channel = Self::get_major_event_channel(data);
         let user = &user_id.to_user(&ctx.http).await?;
 
-        target_channel
-            .send_message(&ctx.http, |m| {
-                m.embed(|e| {
-                    let mut author = CreateEmbedAuthor::default();
-                    author.icon_url(get_avatar_url(user));
-                    author.name(&user.name);
-
-                    let mut footer = CreateEmbedFooter::default();
-                    footer.text(format!("User ID: {}", user.id));
-
-                    let account_age = date_diff(&user.created_at());
-
-                    e.title("Member Joined")
-                        .color(GREEN)
-                        .description(format!("{}", user_id.get_interactive()))
-                        .image(get_avatar_url(&user))
-                        .timestamp(Utc::now())
-                        .set_author(author)
-                        .field("Account Age", account_age, true)
-                        .set_footer(footer)
-                })
-            })
-            .await?;
+        let mut e = CreateEmbed::default();
+        let mut author = CreateEmbedAuthor::default();
+        author.icon_url(get_avatar_url(user));
+        author.name(&user.name);
+
+        let mut footer = CreateEmbedFooter::default();
+        footer.text(format!("User ID: {}", user.id));
+
+        let account_age = date_diff(&user.created_at());
+
+        let record = LogRecord::new(LogKind::UserJoin).user(user_id).change(
+            None::<u64>,
+            serde_json::json!({
+                "account_created": user.created_at().unix_timestamp(),
+                "invite_code": invite.as_ref().map(|x| x.code.clone()),
+                "inviter_id": invite.as_ref().and_then(|x| x.inviter.map(|x| x.0.to_string())),
+            }),
+        );
+
+        MemberHistory::record(
+            data,
+            user_id,
+            MemberHistoryEvent::Joined {
+                invite: invite.as_ref().map(|x| x.code.clone()),
+                inviter: invite.as_ref().and_then(|x| x.inviter.map(|x| x.0)),
+            },
+        )
+        .await?;
+
+        let (invite, inviter) = match invite {
+            Some(invite) => (
+                format!("discord.gg/{}", invite.code),
+                invite
+                    .inviter
+                    .map(|x| x.get_interactive())
+                    .unwrap_or("*Unknown*".to_string()),
+            ),
+            None => (
+                "*Unknown, a vanity url or an invite that could not be told apart*".to_string(),
+                "*Unknown*".to_string(),
+            ),
+        };
+
+        e.title("Member Joined")
+            .color(GREEN)
+            .description(user_id.get_interactive())
+            .image(get_avatar_url(user))
+            .timestamp(Utc::now())
+            .set_author(author)
+            .field("Account Age", account_age, true)
+            .field("Invite", invite, true)
+            .field("Invited By", inviter, true)
+            .set_footer(footer);
+
+        post_embed(data, record, e)?;
 
         Ok(())
     }
@@ -109,43 +221,69 @@ impl UserEvent {
         ctx: &serenity::Context,
         data: &Data,
         user_id: UserId,
-        all_roles: Vec<Role>,
+        departed: &DepartedMember,
     ) -> Result<(), crate::Error> {
-        let target_channel = Self::get_major_event_channel(data);
         let user = &user_id.to_user(&ctx.http).await?;
+        let role_ids: Vec<String> = departed.roles.iter().map(|x| x.0.to_string()).collect();
 
-        let all_roles: Vec<String> = all_roles
-            .iter()
-            .map(|x| x.id)
-            .map(|x| x.get_interactive())
-            .collect();
-
-        let all_roles = all_roles.join(" ");
-
-        target_channel
-            .send_message(&ctx.http, |m| {
-                m.embed(|e| {
-                    let mut author = CreateEmbedAuthor::default();
-                    author.icon_url(get_avatar_url(user));
-                    author.name(&user.name);
-
-                    let mut footer = CreateEmbedFooter::default();
-                    footer.text(format!("User ID: {}", user.id));
-
-                    let account_age = date_diff(&user.created_at());
-
-                    e.title("Member Left")
-                        .color(RED)
-                        .description(format!("{}", user.id.get_interactive()))
-                        .image("https://i.ibb.co/1qyVmzG/left-discord.png")
-                        .timestamp(Utc::now())
-                        .set_author(author)
-                        .field("Account Age", account_age, true)
-                        .field("Roles", all_roles, false)
-                        .set_footer(footer)
-                })
-            })
-            .await?;
+        MemberHistory::record(
+            data,
+            user_id,
+            MemberHistoryEvent::Left {
+                roles: departed.roles.iter().map(|x| x.0).collect(),
+            },
+        )
+        .await?;
+
+        let all_roles: Vec<String> = departed.roles.iter().map(|x| x.get_interactive()).collect();
+
+        let all_roles = match all_roles.is_empty() {
+            true => "*None known*".to_string(),
+            false => all_roles.join(" "),
+        };
+
+        let tenure = departed
+            .joined_at
+            .map(|x| date_diff(&x))
+            .unwrap_or("*Unknown*".to_string());
+
+        let last_seen = departed
+            .last_seen
+            .map(|x| format!("<t:{}:R>", x))
+            .unwrap_or("*Unknown*".to_string());
+
+        let mut e = CreateEmbed::default();
+        let mut author = CreateEmbedAuthor::default();
+        author.icon_url(get_avatar_url(user));
+        author.name(&user.name);
+
+        let mut footer = CreateEmbedFooter::default();
+        footer.text(format!("User ID: {}", user.id));
+
+        let account_age = date_diff(&user.created_at());
+
+        e.title("Member Left")
+            .color(RED)
+            .description(user.id.get_interactive())
+            .image("https://i.ibb.co/1qyVmzG/left-discord.png")
+            .timestamp(Utc::now())
+            .set_author(author)
+            .field("Account Age", account_age, true)
+            .field("Member For", tenure, true)
+            .field("Last Seen", last_seen, true)
+            .field("Roles", all_roles, false)
+            .set_footer(footer);
+
+        let record = LogRecord::new(LogKind::UserLeave).user(user_id).change(
+            serde_json::json!({
+                "roles": role_ids,
+                "joined_at": departed.joined_at.map(|x| x.unix_timestamp()),
+                "last_seen": departed.last_seen,
+            }),
+            None::<u64>,
+        );
+
+        post_embed(data, record, e)?;
 
         Ok(())
     }
@@ -153,34 +291,49 @@ impl UserEvent {
     async fn execute_user_ban_guild_log(
         ctx: &serenity::Context,
         data: &Data,
+        guild_id: GuildId,
         user_id: UserId,
     ) -> Result<(), crate::Error> {
-        let target_channel = Self::get_major_event_channel(data);
         let user = &user_id.to_user(&ctx.http).await?;
 
-        target_channel
-            .send_message(&ctx.http, |m| {
-                m.embed(|e| {
-                    let mut author = CreateEmbedAuthor::default();
-                    author.icon_url(get_avatar_url(user));
-                    author.name(&user.name);
-
-                    let mut footer = CreateEmbedFooter::default();
-                    footer.text(format!("User ID: {}", user.id));
-
-                    let account_age = date_diff(&user.created_at());
-
-                    e.title("Member Banned")
-                        .color(RED)
-                        .description(format!("{}", user.id.get_interactive()))
-                        .image("https://i.ibb.co/P4m8YSL/banned.png")
-                        .timestamp(Utc::now())
-                        .set_author(author)
-                        .field("Account Age", account_age, true)
-                        .set_footer(footer)
-                })
-            })
-            .await?;
+        let mut e = CreateEmbed::default();
+        let mut author = CreateEmbedAuthor::default();
+        author.icon_url(get_avatar_url(user));
+        author.name(&user.name);
+
+        let mut footer = CreateEmbedFooter::default();
+        footer.text(format!("User ID: {}", user.id));
+
+        let account_age = date_diff(&user.created_at());
+
+        e.title("Member Banned")
+            .color(RED)
+            .description(user.id.get_interactive())
+            .image("https://i.ibb.co/P4m8YSL/banned.png")
+            .timestamp(Utc::now())
+            .set_author(author)
+            .field("Account Age", account_age, true)
+            .set_footer(footer);
+
+        let attribution = Attribution::lookup(ctx, guild_id, AuditedChange::Ban, user_id).await;
+        attribution.add_fields(&mut e);
+
+        MemberHistory::record(
+            data,
+            user_id,
+            MemberHistoryEvent::Banned {
+                moderator: attribution.moderator().map(|x| x.0),
+                reason: attribution.reason().map(|x| x.to_string()),
+            },
+        )
+        .await?;
+
+        let record = LogRecord::new(LogKind::UserBan)
+            .user(user_id)
+            .actor(attribution.moderator())
+            .change(false, serde_json::json!({ "reason": attribution.reason() }));
+
+        post_embed(data, record, e)?;
 
         Ok(())
     }
@@ -190,42 +343,44 @@ impl UserEvent {
         data: &Data,
         user_id: UserId,
     ) -> Result<(), crate::Error> {
-        let target_channel = Self::get_major_event_channel(data);
         let user = &user_id.to_user(&ctx.http).await?;
 
-        target_channel
-            .send_message(&ctx.http, |m| {
-                m.embed(|e| {
-                    let mut author = CreateEmbedAuthor::default();
-                    author.icon_url(get_avatar_url(user));
-                    author.name(&user.name);
-
-                    let mut footer = CreateEmbedFooter::default();
-                    footer.text(format!("User ID: {}", user.id));
-
-                    let account_age = date_diff(&user.created_at());
-
-                    e.title("Member Unbanned")
-                        .color(GREEN)
-                        .description(format!("{}", user.id.get_interactive()))
-                        .image("https://i.ibb.co/7nqVFKd/unbanned.png")
-                        .timestamp(Utc::now())
-                        .set_author(author)
-                        .field("Account Age", account_age, true)
-                        .set_footer(footer)
-                })
-            })
-            .await?;
+        let mut e = CreateEmbed::default();
+        let mut author = CreateEmbedAuthor::default();
+        author.icon_url(get_avatar_url(user));
+        author.name(&user.name);
+
+        let mut footer = CreateEmbedFooter::default();
+        footer.text(format!("User ID: {}", user.id));
+
+        let account_age = date_diff(&user.created_at());
+
+        e.title("Member Unbanned")
+            .color(GREEN)
+            .description(user.id.get_interactive())
+            .image("https://i.ibb.co/7nqVFKd/unbanned.png")
+            .timestamp(Utc::now())
+            .set_author(author)
+            .field("Account Age", account_age, true)
+            .set_footer(footer);
+
+        MemberHistory::record(data, user_id, MemberHistoryEvent::Unbanned).await?;
+
+        let record = LogRecord::new(LogKind::UserUnban)
+            .user(user_id)
+            .change(true, false);
+
+        post_embed(data, record, e)?;
         Ok(())
     }
 
     async fn execute_user_roles_changed_log(
         ctx: &serenity::Context,
         data: &Data,
+        guild_id: GuildId,
         user_id: UserId,
         role_state: &RoleState,
     ) -> Result<(), crate::Error> {
-        let target_channel = Self::get_major_event_channel(data);
         let user = &user_id.to_user(&ctx.http).await?;
 
         let new_roles: String = role_state
@@ -242,37 +397,58 @@ impl UserEvent {
             .collect();
         let old_roles = old_roles.replace("><", "> <");
 
-        target_channel
-            .send_message(&ctx.http, |m| {
-                m.embed(|e| {
-                    let mut author = CreateEmbedAuthor::default();
-                    author.icon_url(get_avatar_url(user));
-                    author.name(&user.name);
+        let mut e = CreateEmbed::default();
+        let mut author = CreateEmbedAuthor::default();
+        author.icon_url(get_avatar_url(user));
+        author.name(&user.name);
 
-                    let mut footer = CreateEmbedFooter::default();
-                    footer.text(format!("User ID: {}", user.id));
+        let mut footer = CreateEmbedFooter::default();
+        footer.text(format!("User ID: {}", user.id));
 
-                    let embed = e
-                        .title("Roles Updated")
-                        .color(YELLOW)
-                        .description("🔄 🔄 🔄");
+        let embed = e
+            .title("Roles Updated")
+            .color(YELLOW)
+            .description("🔄 🔄 🔄");
 
-                    if new_roles.len() > 0 {
-                        embed.field("New Roles: ", new_roles, false);
-                    }
+        if !new_roles.is_empty() {
+            embed.field("New Roles: ", new_roles, false);
+        }
 
-                    if old_roles.len() > 0 {
-                        embed.field("Removed Roles: ", old_roles, false);
-                    }
+        if !old_roles.is_empty() {
+            embed.field("Removed Roles: ", old_roles, false);
+        }
+
+        embed
+            .timestamp(Utc::now())
+            .set_author(author)
+            .field("Username", user.id.get_interactive(), false)
+            .set_footer(footer);
+
+        let attribution = Attribution::lookup(ctx, guild_id, AuditedChange::Roles, user_id).await;
+        attribution.add_fields(&mut e);
+
+        MemberHistory::record(
+            data,
+            user_id,
+            MemberHistoryEvent::RolesChanged {
+                added: role_state.added.iter().map(|x| x.0).collect(),
+                removed: role_state.removed.iter().map(|x| x.0).collect(),
+                moderator: attribution.moderator().map(|x| x.0),
+            },
+        )
+        .await?;
+
+        let ids =
+            |roles: &[RoleId]| -> Vec<String> { roles.iter().map(|x| x.0.to_string()).collect() };
+        let record = LogRecord::new(LogKind::RoleChange)
+            .user(user_id)
+            .actor(attribution.moderator())
+            .change(
+                serde_json::json!({ "removed": ids(&role_state.removed) }),
+                serde_json::json!({ "added": ids(&role_state.added) }),
+            );
 
-                    embed
-                        .timestamp(Utc::now())
-                        .set_author(author)
-                        .field("Username", format!("{}", user.id.get_interactive()), false)
-                        .set_footer(footer)
-                })
-            })
-            .await?;
+        post_embed(data, record, e)?;
 
         Ok(())
     }
@@ -280,45 +456,198 @@ impl UserEvent {
     async fn execute_user_nickname_changed_log(
         ctx: &serenity::Context,
         data: &Data,
+        guild_id: GuildId,
         user_id: UserId,
         old_nickname: &Option<String>,
         new_nickname: &Option<String>,
     ) -> Result<(), crate::Error> {
-        let target_channel = Self::get_major_event_channel(data);
         let user = &user_id.to_user(&ctx.http).await?;
 
-        target_channel
-            .send_message(&ctx.http, |m| {
-                m.embed(|e| {
-                    let mut author = CreateEmbedAuthor::default();
-                    author.icon_url(get_avatar_url(user));
-                    author.name(&user.name);
+        let mut e = CreateEmbed::default();
+        let mut author = CreateEmbedAuthor::default();
+        author.icon_url(get_avatar_url(user));
+        author.name(&user.name);
 
-                    let mut footer = CreateEmbedFooter::default();
-                    footer.text(format!("User ID: {}", user.id));
+        let mut footer = CreateEmbedFooter::default();
+        footer.text(format!("User ID: {}", user.id));
 
-                    let embed = e.title("Nickname Changed").color(YELLOW);
+        let embed = e.title("Nickname Changed").color(YELLOW);
 
-                    if let Some(old_nickname) = old_nickname {
-                        embed.field("Old Nickname", old_nickname, true);
-                    } else {
-                        embed.field("Old Nickname", "*Default Nickname*", true);
-                    }
+        if let Some(old_nickname) = old_nickname {
+            embed.field("Old Nickname", old_nickname, true);
+        } else {
+            embed.field("Old Nickname", "*Default Nickname*", true);
+        }
 
-                    if let Some(new_nickname) = new_nickname {
-                        embed.field("New Nickname", new_nickname, true);
-                    } else {
-                        embed.field("New Nickname", "*Default Nickname*", true);
-                    }
+        if let Some(new_nickname) = new_nickname {
+            embed.field("New Nickname", new_nickname, true);
+        } else {
+            embed.field("New Nickname", "*Default Nickname*", true);
+        }
 
-                    embed
-                        .timestamp(Utc::now())
-                        .set_author(author)
-                        .field("Username", format!("{}", user.id.get_interactive()), false)
-                        .set_footer(footer)
-                })
-            })
-            .await?;
+        embed
+            .timestamp(Utc::now())
+            .set_author(author)
+            .field("Username", user.id.get_interactive(), false)
+            .set_footer(footer);
+
+        let attribution =
+            Attribution::lookup(ctx, guild_id, AuditedChange::Nickname, user_id).await;
+        attribution.add_fields(&mut e);
+
+        MemberHistory::record(
+            data,
+            user_id,
+            MemberHistoryEvent::NicknameChanged {
+                old: old_nickname.clone(),
+                new: new_nickname.clone(),
+                moderator: attribution.moderator().map(|x| x.0),
+            },
+        )
+        .await?;
+
+        let record = LogRecord::new(LogKind::NicknameChange)
+            .user(user_id)
+            .actor(attribution.moderator())
+            .change(old_nickname, new_nickname);
+
+        post_embed(data, record, e)?;
+
+        Ok(())
+    }
+
+    async fn execute_user_timeout_changed_log(
+        ctx: &serenity::Context,
+        data: &Data,
+        guild_id: GuildId,
+        user_id: UserId,
+        old_until: &Option<Timestamp>,
+        new_until: &Option<Timestamp>,
+    ) -> Result<(), crate::Error> {
+        let user = &user_id.to_user(&ctx.http).await?;
+
+        let mut e = CreateEmbed::default();
+        let mut author = CreateEmbedAuthor::default();
+        author.icon_url(get_avatar_url(user));
+        author.name(&user.name);
+
+        let mut footer = CreateEmbedFooter::default();
+        footer.text(format!("User ID: {}", user.id));
+
+        // An expired timeout is cleared the same way a lifted one is
+        let active_until = new_until.filter(|x| x.unix_timestamp() > Utc::now().timestamp());
+        match active_until {
+            Some(until) => e.title("Member Timed Out").color(RED).field(
+                "Until",
+                format!("<t:{}:f>", until.unix_timestamp()),
+                true,
+            ),
+            None => {
+                e.title("Timeout Removed").color(GREEN);
+
+                if let Some(old_until) = old_until {
+                    e.field(
+                        "Was Until",
+                        format!("<t:{}:f>", old_until.unix_timestamp()),
+                        true,
+                    );
+                }
+
+                &mut e
+            }
+        };
+
+        e.timestamp(Utc::now())
+            .set_author(author)
+            .field("Username", user.id.get_interactive(), false)
+            .set_footer(footer);
+
+        let attribution = Attribution::lookup(ctx, guild_id, AuditedChange::Timeout, user_id).await;
+        attribution.add_fields(&mut e);
+
+        let until = |x: &Option<Timestamp>| x.map(|x| x.unix_timestamp());
+        let record = LogRecord::new(LogKind::MemberTimeout)
+            .user(user_id)
+            .actor(attribution.moderator())
+            .change(until(old_until), until(new_until));
+
+        post_embed(data, record, e)?;
+
+        Ok(())
+    }
+
+    /// Changes the member makes themselves or that Discord applies, so nobody is attributed
+    async fn execute_user_member_state_log(
+        ctx: &serenity::Context,
+        data: &Data,
+        user_id: UserId,
+        change: &UserChangeType,
+    ) -> Result<(), crate::Error> {
+        let user = &user_id.to_user(&ctx.http).await?;
+
+        let mut e = CreateEmbed::default();
+        let mut author = CreateEmbedAuthor::default();
+        author.icon_url(get_avatar_url(user));
+        author.name(&user.name);
+
+        let mut footer = CreateEmbedFooter::default();
+        footer.text(format!("User ID: {}", user.id));
+
+        let record = match change {
+            UserChangeType::GuildAvatarChanged(old_avatar, new_avatar) => {
+                e.title("Server Avatar Changed").color(YELLOW);
+
+                match old_avatar {
+                    Some(old_avatar) => e.thumbnail(old_avatar),
+                    None => e.field("Old Avatar", "*Default Avatar*", true),
+                };
+
+                match new_avatar {
+                    Some(new_avatar) => e.image(new_avatar),
+                    None => e.field("New Avatar", "*Default Avatar*", true),
+                };
+
+                LogRecord::new(LogKind::GuildAvatarChange).change(old_avatar, new_avatar)
+            }
+            UserChangeType::BoostChanged(Some(since)) => {
+                e.title("Started Boosting").color(FUCHSIA).field(
+                    "Since",
+                    format!("<t:{}:f>", since.unix_timestamp()),
+                    true,
+                );
+
+                LogRecord::new(LogKind::BoostChange).change(
+                    serde_json::json!({ "boosting": false }),
+                    serde_json::json!({ "boosting": true, "since": since.unix_timestamp() }),
+                )
+            }
+            UserChangeType::BoostChanged(None) => {
+                e.title("Stopped Boosting").color(RED);
+
+                LogRecord::new(LogKind::BoostChange).change(
+                    serde_json::json!({ "boosting": true }),
+                    serde_json::json!({ "boosting": false, "since": null }),
+                )
+            }
+            UserChangeType::PendingChanged(true) => {
+                e.title("Membership Screening Pending").color(YELLOW);
+
+                LogRecord::new(LogKind::MembershipScreening).change(false, true)
+            }
+            UserChangeType::PendingChanged(false) => {
+                e.title("Passed Membership Screening").color(GREEN);
+
+                LogRecord::new(LogKind::MembershipScreening).change(true, false)
+            }
+            _ => return Ok(()),
+        };
+
+        e.timestamp(Utc::now())
+            .set_author(author)
+            .field("Username", user.id.get_interactive(), false)
+            .set_footer(footer);
+
+        post_embed(data, record.user(user_id), e)?;
 
         Ok(())
     }
@@ -332,41 +661,52 @@ impl UserEvent {
         data: &Data,
     ) -> Result<(), crate::Error> {
         match self {
-            Self::UserJoin(user_id) => {
-                Self::execute_user_joined_guild_log(ctx, data, *user_id).await?;
+            Self::UserJoin(user_id, invite) => {
+                Self::execute_user_joined_guild_log(ctx, data, *user_id, invite).await?;
             }
-            Self::UserLeave(user_id, all_roles) => {
-                Self::execute_user_left_guild_log(ctx, data, *user_id, all_roles.clone()).await?;
+            Self::UserLeave(user_id, departed) => {
+                Self::execute_user_left_guild_log(ctx, data, *user_id, departed).await?;
             }
-            Self::UserBan(user_id) => {
-                Self::execute_user_ban_guild_log(ctx, data, *user_id).await?;
+            Self::UserBan(guild_id, user_id) => {
+                Self::execute_user_ban_guild_log(ctx, data, *guild_id, *user_id).await?;
             }
             Self::UserUnban(user_id) => {
                 Self::execute_user_unban_guild_log(ctx, data, *user_id).await?;
             }
-            Self::UserChange(user_id, user_change_type) => match user_change_type {
-                UserChangeType::RolesChanged(role_state) => {
-                    Self::execute_user_roles_changed_log(ctx, data, *user_id, role_state).await?;
-                }
-                UserChangeType::NickNameChanged(old_nickname, new_nickname) => {
-                    Self::execute_user_nickname_changed_log(
-                        ctx,
-                        data,
-                        *user_id,
-                        old_nickname,
-                        new_nickname,
-                    )
-                    .await?
+            Self::UserChange(guild_id, user_id, changes) => {
+                for user_change_type in changes {
+                    match user_change_type {
+                        UserChangeType::RolesChanged(role_state) => {
+                            Self::execute_user_roles_changed_log(
+                                ctx, data, *guild_id, *user_id, role_state,
+                            )
+                            .await?;
+                        }
+                        UserChangeType::NickNameChanged(old_nickname, new_nickname) => {
+                            Self::execute_user_nickname_changed_log(
+                                ctx,
+                                data,
+                                *guild_id,
+                                *user_id,
+                                old_nickname,
+                                new_nickname,
+                            )
+                            .await?
+                        }
+                        UserChangeType::TimeoutChanged(old_until, new_until) => {
+                            Self::execute_user_timeout_changed_log(
+                                ctx, data, *guild_id, *user_id, old_until, new_until,
+                            )
+                            .await?
+                        }
+                        change => {
+                            Self::execute_user_member_state_log(ctx, data, *user_id, change).await?
+                        }
+                    }
                 }
-                UserChangeType::Unknown => (),
-            },
+            }
         }
 
         Ok(())
     }
-
-    fn get_major_event_channel(data: &Data) -> ChannelId {
-        ChannelId::from_str(data.major_events_channel.as_str())
-            .expect("MINOR_EVENTS_CHANNEL secret could not be parsed into a u64")
-    }
 }
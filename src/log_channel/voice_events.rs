@@ -1,21 +1,123 @@
-use std::str::FromStr;
-
 use chrono::Utc;
 use poise::serenity_prelude::{
     self as serenity,
     colours::branding::{GREEN, RED, YELLOW},
-    ChannelId, CreateEmbedAuthor, CreateEmbedFooter, UserId, VoiceState,
+    ChannelId, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, GuildId, UserId, VoiceState,
 };
 
-use crate::{extensions::InteractiveSnowflakeExt, state::Data, utils::discord_cdn::get_avatar_url};
+use crate::{
+    extensions::InteractiveSnowflakeExt,
+    log_channel::{
+        attribution::{Attribution, AuditedChange},
+        dispatcher::Coalesce,
+        post_coalesced_embed, post_embed,
+        sinks::LogRecord,
+    },
+    state::{log_routes::LogKind, voice_stats::VoiceStats, Data},
+    utils::{discord_cdn::get_avatar_url, time::format_duration},
+};
 
 pub enum VoiceEvent {
     UserJoinedChannel(ChannelId, UserId),
     UserLeftChannel(ChannelId, UserId),
     UserMovedChannel(ChannelId, ChannelId, UserId),
+    UserStateChanged(Option<GuildId>, ChannelId, UserId, Vec<VoiceStateChange>),
     Unknown,
 }
 
+/// A voice state flag that flipped while the user stayed in the same channel, holding its new value
+#[derive(Clone, Copy)]
+pub enum VoiceStateChange {
+    SelfMute(bool),
+    SelfDeafen(bool),
+    ServerMute(bool),
+    ServerDeafen(bool),
+    Streaming(bool),
+    Video(bool),
+    /// Stage channels only, suppressed users sit in the audience
+    Suppress(bool),
+}
+
+impl VoiceStateChange {
+    fn all(old: &VoiceState, new: &VoiceState) -> Vec<Self> {
+        let flags = [
+            (
+                old.self_mute,
+                new.self_mute,
+                Self::SelfMute as fn(bool) -> Self,
+            ),
+            (old.self_deaf, new.self_deaf, Self::SelfDeafen),
+            (old.mute, new.mute, Self::ServerMute),
+            (old.deaf, new.deaf, Self::ServerDeafen),
+            (
+                old.self_stream.unwrap_or(false),
+                new.self_stream.unwrap_or(false),
+                Self::Streaming,
+            ),
+            (old.self_video, new.self_video, Self::Video),
+            (old.suppress, new.suppress, Self::Suppress),
+        ];
+
+        flags
+            .into_iter()
+            .filter(|(old, new, _)| old != new)
+            .map(|(_, new, change)| change(new))
+            .collect()
+    }
+
+    fn kind(&self) -> LogKind {
+        match self {
+            Self::SelfMute(_) => LogKind::VoiceSelfMute,
+            Self::SelfDeafen(_) => LogKind::VoiceSelfDeafen,
+            Self::ServerMute(_) => LogKind::VoiceServerMute,
+            Self::ServerDeafen(_) => LogKind::VoiceServerDeafen,
+            Self::Streaming(_) => LogKind::VoiceStream,
+            Self::Video(_) => LogKind::VoiceVideo,
+            Self::Suppress(_) => LogKind::VoiceSuppress,
+        }
+    }
+
+    fn value(&self) -> bool {
+        match *self {
+            Self::SelfMute(value)
+            | Self::SelfDeafen(value)
+            | Self::ServerMute(value)
+            | Self::ServerDeafen(value)
+            | Self::Streaming(value)
+            | Self::Video(value)
+            | Self::Suppress(value) => value,
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            Self::SelfMute(true) => "Self Muted",
+            Self::SelfMute(false) => "Self Unmuted",
+            Self::SelfDeafen(true) => "Self Deafened",
+            Self::SelfDeafen(false) => "Self Undeafened",
+            Self::ServerMute(true) => "Server Muted",
+            Self::ServerMute(false) => "Server Unmuted",
+            Self::ServerDeafen(true) => "Server Deafened",
+            Self::ServerDeafen(false) => "Server Undeafened",
+            Self::Streaming(true) => "Started Streaming",
+            Self::Streaming(false) => "Stopped Streaming",
+            Self::Video(true) => "Turned Camera On",
+            Self::Video(false) => "Turned Camera Off",
+            Self::Suppress(true) => "Moved to Audience",
+            Self::Suppress(false) => "Became Stage Speaker",
+        }
+    }
+
+    /// Server mutes and deafens are done by a moderator, which the audit log can tell
+    fn audited(&self) -> Option<AuditedChange> {
+        match self {
+            Self::ServerMute(_) => Some(AuditedChange::Mute),
+            Self::ServerDeafen(_) => Some(AuditedChange::Deafen),
+            _ => None,
+        }
+    }
+}
+
 // Log channel functionality
 impl VoiceEvent {
     async fn execute_user_joined_vc_log(
@@ -24,33 +126,30 @@ impl VoiceEvent {
         data: &Data,
         user_id: UserId,
     ) -> Result<(), crate::Error> {
-        let target_channel = Self::get_minor_event_channel(data);
         let user = &user_id.to_user(&ctx.http).await?;
 
-        target_channel
-            .send_message(&ctx.http, |m| {
-                m.embed(|e| {
-                    let mut author = CreateEmbedAuthor::default();
-                    author.icon_url(get_avatar_url(user));
-                    author.name(&user.name);
-
-                    let mut footer = CreateEmbedFooter::default();
-                    footer.text(format!("User ID: {}", user_id));
-
-                    e.title("Joined Voice Chat")
-                        .color(GREEN)
-                        .description(format!("Channel: {}", channel_id.get_interactive()))
-                        .timestamp(Utc::now())
-                        .set_author(author)
-                        .field(
-                            "Display Name",
-                            format!("{}", user_id.get_interactive(),),
-                            false,
-                        )
-                        .set_footer(footer)
-                })
-            })
-            .await?;
+        let mut e = CreateEmbed::default();
+        let mut author = CreateEmbedAuthor::default();
+        author.icon_url(get_avatar_url(user));
+        author.name(&user.name);
+
+        let mut footer = CreateEmbedFooter::default();
+        footer.text(format!("User ID: {}", user_id));
+
+        e.title("Joined Voice Chat")
+            .color(GREEN)
+            .description(format!("Channel: {}", channel_id.get_interactive()))
+            .timestamp(Utc::now())
+            .set_author(author)
+            .field("Display Name", user_id.get_interactive(), false)
+            .set_footer(footer);
+
+        let line = format!("Joined {}", channel_id.get_interactive());
+        let record = LogRecord::new(LogKind::VoiceJoin)
+            .user(user_id)
+            .channel(*channel_id)
+            .change(None::<u64>, Some(channel_id.0.to_string()));
+        post_coalesced_embed(data, record, e, Self::coalesce(user_id, line))?;
         Ok(())
     }
 
@@ -59,34 +158,42 @@ impl VoiceEvent {
         ctx: &serenity::Context,
         data: &Data,
         user_id: UserId,
+        duration: Option<i64>,
     ) -> Result<(), crate::Error> {
-        let target_channel = Self::get_minor_event_channel(data);
         let user = &user_id.to_user(&ctx.http).await?;
 
-        target_channel
-            .send_message(&ctx.http, |m| {
-                m.embed(|e| {
-                    let mut author = CreateEmbedAuthor::default();
-                    author.icon_url(get_avatar_url(user));
-                    author.name(&user.name);
-
-                    let mut footer = CreateEmbedFooter::default();
-                    footer.text(format!("User ID: {}", user_id));
-
-                    e.title("Left Voice Chat")
-                        .color(RED)
-                        .description(format!("Channel: {}", channel_id.get_interactive()))
-                        .timestamp(Utc::now())
-                        .set_author(author)
-                        .field(
-                            "Display Name",
-                            format!("{}", user_id.get_interactive(),),
-                            false,
-                        )
-                        .set_footer(footer)
-                })
-            })
-            .await?;
+        let mut e = CreateEmbed::default();
+        let mut author = CreateEmbedAuthor::default();
+        author.icon_url(get_avatar_url(user));
+        author.name(&user.name);
+
+        let mut footer = CreateEmbedFooter::default();
+        footer.text(format!("User ID: {}", user_id));
+
+        let record = LogRecord::new(LogKind::VoiceLeave)
+            .user(user_id)
+            .channel(*channel_id)
+            .change(
+                Some(channel_id.0.to_string()),
+                serde_json::json!({ "session_seconds": duration }),
+            );
+
+        let duration = match duration {
+            Some(duration) => format_duration(duration),
+            None => "*Unknown, joined before the bot started tracking*".to_string(),
+        };
+
+        e.title("Left Voice Chat")
+            .color(RED)
+            .description(format!("Channel: {}", channel_id.get_interactive()))
+            .timestamp(Utc::now())
+            .set_author(author)
+            .field("Display Name", user_id.get_interactive(), false)
+            .field("Time in channel", duration, false)
+            .set_footer(footer);
+
+        let line = format!("Left {}", channel_id.get_interactive());
+        post_coalesced_embed(data, record, e, Self::coalesce(user_id, line))?;
         Ok(())
     }
 
@@ -97,40 +204,104 @@ impl VoiceEvent {
         data: &Data,
         user_id: UserId,
     ) -> Result<(), crate::Error> {
-        let target_channel = Self::get_minor_event_channel(data);
         let user = &user_id.to_user(&ctx.http).await?;
 
-        target_channel
-            .send_message(&ctx.http, |m| {
-                m.embed(|e| {
-                    let mut author = CreateEmbedAuthor::default();
-                    author.icon_url(get_avatar_url(user));
-                    author.name(&user.name);
-
-                    let mut footer = CreateEmbedFooter::default();
-                    footer.text(format!("User ID: {}", user_id));
-
-                    e.title("Moved Voice Chat")
-                        .color(YELLOW)
-                        .field("Left", old_channel_id.get_interactive(), true)
-                        .field("Joined", new_channel_id.get_interactive(), true)
-                        .timestamp(Utc::now())
-                        .set_author(author)
-                        .field(
-                            "Display Name",
-                            format!("{}", user_id.get_interactive(),),
-                            false,
-                        )
-                        .set_footer(footer)
-                })
-            })
-            .await?;
+        let mut e = CreateEmbed::default();
+        let mut author = CreateEmbedAuthor::default();
+        author.icon_url(get_avatar_url(user));
+        author.name(&user.name);
+
+        let mut footer = CreateEmbedFooter::default();
+        footer.text(format!("User ID: {}", user_id));
+
+        e.title("Moved Voice Chat")
+            .color(YELLOW)
+            .field("Left", old_channel_id.get_interactive(), true)
+            .field("Joined", new_channel_id.get_interactive(), true)
+            .timestamp(Utc::now())
+            .set_author(author)
+            .field("Display Name", user_id.get_interactive(), false)
+            .set_footer(footer);
+
+        let line = format!(
+            "Moved {} -> {}",
+            old_channel_id.get_interactive(),
+            new_channel_id.get_interactive()
+        );
+        let record = LogRecord::new(LogKind::VoiceMove)
+            .user(user_id)
+            .channel(*new_channel_id)
+            .change(old_channel_id.0.to_string(), new_channel_id.0.to_string());
+        post_coalesced_embed(data, record, e, Self::coalesce(user_id, line))?;
+        Ok(())
+    }
+
+    async fn execute_user_state_changed_log(
+        guild_id: Option<GuildId>,
+        channel_id: &ChannelId,
+        ctx: &serenity::Context,
+        data: &Data,
+        user_id: UserId,
+        changes: &[VoiceStateChange],
+    ) -> Result<(), crate::Error> {
+        let user = &user_id.to_user(&ctx.http).await?;
+
+        for change in changes {
+            let mut e = CreateEmbed::default();
+            let mut author = CreateEmbedAuthor::default();
+            author.icon_url(get_avatar_url(user));
+            author.name(&user.name);
+
+            let mut footer = CreateEmbedFooter::default();
+            footer.text(format!("User ID: {}", user_id));
+
+            e.title(change.title())
+                .color(YELLOW)
+                .description(format!("Channel: {}", channel_id.get_interactive()))
+                .timestamp(Utc::now())
+                .set_author(author)
+                .field("Display Name", user_id.get_interactive(), false)
+                .set_footer(footer);
+
+            let record = LogRecord::new(change.kind())
+                .user(user_id)
+                .channel(*channel_id)
+                .change(!change.value(), change.value());
+
+            match (change.audited(), guild_id) {
+                (Some(audited), Some(guild_id)) => {
+                    let attribution = Attribution::lookup(ctx, guild_id, audited, user_id).await;
+                    attribution.add_fields(&mut e);
+
+                    let record = record.actor(attribution.moderator());
+                    post_embed(data, record, e)?;
+                }
+                (Some(_), None) => {
+                    Attribution::Unknown.add_fields(&mut e);
+                    post_embed(data, record, e)?;
+                }
+                (None, _) => {
+                    let line = format!("{} in {}", change.title(), channel_id.get_interactive());
+                    post_coalesced_embed(data, record, e, Self::coalesce(user_id, line))?;
+                }
+            }
+        }
+
         Ok(())
     }
 }
 
 // Core functionality
 impl VoiceEvent {
+    /// Rapid channel hops of one user end up in a single embed
+    fn coalesce(user_id: UserId, line: String) -> Coalesce {
+        Coalesce {
+            key: format!("voice-{}", user_id),
+            title: "Voice Activity".to_string(),
+            line,
+        }
+    }
+
     pub fn new(old: &Option<VoiceState>, new: &VoiceState) -> Self {
         if Self::is_user_joined(old, new) {
             Self::UserJoinedChannel(new.channel_id.unwrap(), new.user_id)
@@ -142,6 +313,13 @@ impl VoiceEvent {
                 new.channel_id.unwrap(),
                 new.user_id,
             )
+        } else if let Some(old) = old.as_ref().filter(|old| old.channel_id == new.channel_id) {
+            match (new.channel_id, VoiceStateChange::all(old, new)) {
+                (Some(channel_id), changes) if !changes.is_empty() => {
+                    Self::UserStateChanged(new.guild_id, channel_id, new.user_id, changes)
+                }
+                _ => Self::Unknown,
+            }
         } else {
             Self::Unknown
         }
@@ -152,14 +330,19 @@ impl VoiceEvent {
         ctx: &serenity::Context,
         data: &Data,
     ) -> Result<(), crate::Error> {
+        let now = Utc::now().timestamp();
+
         match self {
             Self::UserJoinedChannel(channel_id, user_id) => {
+                VoiceStats::join(data, user_id.0, channel_id.0, now).await?;
                 Self::execute_user_joined_vc_log(channel_id, ctx, data, *user_id).await?;
             }
             Self::UserLeftChannel(channel_id, user_id) => {
-                Self::execute_user_left_vc_log(channel_id, ctx, data, *user_id).await?;
+                let duration = VoiceStats::leave(data, user_id.0, now).await?;
+                Self::execute_user_left_vc_log(channel_id, ctx, data, *user_id, duration).await?;
             }
             Self::UserMovedChannel(old_channel_id, new_channel_id, user_id) => {
+                VoiceStats::moved(data, user_id.0, new_channel_id.0, now).await?;
                 Self::execute_user_moved_vc_log(
                     old_channel_id,
                     new_channel_id,
@@ -169,6 +352,12 @@ impl VoiceEvent {
                 )
                 .await?;
             }
+            Self::UserStateChanged(guild_id, channel_id, user_id, changes) => {
+                Self::execute_user_state_changed_log(
+                    *guild_id, channel_id, ctx, data, *user_id, changes,
+                )
+                .await?;
+            }
             Self::Unknown => (),
         }
 
@@ -176,35 +365,24 @@ impl VoiceEvent {
     }
 
     fn is_user_joined(old: &Option<VoiceState>, new: &VoiceState) -> bool {
-        if old.is_none() && new.channel_id.is_some() {
-            true
-        } else {
-            false
-        }
+        old.is_none() && new.channel_id.is_some()
     }
 
     fn is_user_left(old: &Option<VoiceState>, new: &VoiceState) -> bool {
-        if old.is_some() && new.channel_id.is_none() {
-            if old.as_ref().unwrap().channel_id.is_some() {
-                return true;
-            }
+        if old.is_some() && new.channel_id.is_none() && old.as_ref().unwrap().channel_id.is_some() {
+            return true;
         }
         false
     }
 
     fn is_user_moved(old: &Option<VoiceState>, new: &VoiceState) -> bool {
-        if old.is_some() && new.channel_id.is_some() {
-            if old.as_ref().unwrap().channel_id.is_some() {
-                if old.as_ref().unwrap().channel_id.unwrap() != new.channel_id.unwrap() {
-                    return true;
-                }
-            }
+        if old.is_some()
+            && new.channel_id.is_some()
+            && old.as_ref().unwrap().channel_id.is_some()
+            && old.as_ref().unwrap().channel_id.unwrap() != new.channel_id.unwrap()
+        {
+            return true;
         }
         false
     }
-
-    fn get_minor_event_channel(data: &Data) -> ChannelId {
-        ChannelId::from_str(data.minor_events_channel.as_str())
-            .expect("MINOR_EVENTS_CHANNEL secret could not be parsed into a u64")
-    }
 }
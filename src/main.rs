@@ -1,15 +1,27 @@
 use crate::state::init_all_state;
 use anyhow::Context as _;
 use log_channel::{
-    user_events::{UserChangeType, UserEvent},
+    dispatcher::LogDispatcher,
+    guild_events::{ChannelSnapshot, GuildEvent},
+    message_events::{CachedMessage, MessageEvent},
+    sinks::{jsonl::JsonlSink, webhook::WebhookSink, EventSink, EventSinks},
+    startup_events::StartupEvent,
+    thread_events::ThreadEvent,
+    user_events::{DepartedMember, UserChangeType, UserEvent},
     voice_events::VoiceEvent,
 };
-use poise::serenity_prelude::{self as serenity, CacheHttp, GuildId, Interaction, RoleId};
+use message_component_interactions::{
+    game_suggestion_menu::GameSuggestionMenu, quarantine_menu::QuarantineMenu,
+};
+use poise::serenity_prelude::{self as serenity, ActivityType, GuildId, Interaction, RoleId};
 use serenity::GatewayIntents;
 use shuttle_persist::PersistInstance;
 use shuttle_poise::ShuttlePoise;
 use shuttle_secrets::SecretStore;
-use state::Data;
+use state::{
+    game_activity::GameActivity, invite_joins::InviteJoins, member_snapshots::MemberSnapshots,
+    quarantine::Quarantine, t_rooms::TRooms, Data, StateBackend,
+};
 use std::{panic, str::FromStr};
 
 mod checks;
@@ -17,6 +29,7 @@ mod constants;
 mod context_commands;
 mod data_enums;
 mod data_structs;
+mod error;
 mod extensions;
 mod log_channel;
 mod message_component_interactions;
@@ -26,7 +39,7 @@ mod utils;
 
 // User data, which is stored and accessible in all command invocations
 
-pub type Error = Box<dyn std::error::Error + Send + Sync>;
+pub type Error = error::BotError;
 type Context<'a> = poise::Context<'a, Data, Error>;
 
 async fn event_handler(
@@ -39,11 +52,39 @@ async fn event_handler(
         poise::Event::GuildMemberAddition { new_member } => {
             let mut new_member = new_member.clone();
 
-            new_member
-                .add_role(&ctx.http, RoleId::from_str(data.follower_role.as_str())?)
-                .await?;
+            match Quarantine::role_for(data, &new_member.user)? {
+                Some(quarantine_role) => {
+                    new_member.add_role(&ctx.http, quarantine_role).await?;
+                    // The join still has to be logged and its invite tracked
+                    let alert =
+                        QuarantineMenu::post_alert(ctx, data, &new_member, quarantine_role).await;
+                    if let Err(e) = alert {
+                        tracing::error!("Could not post the quarantine alert: {}", e);
+                    }
+                }
+                None => {
+                    new_member
+                        .add_role(&ctx.http, RoleId::from_str(data.follower_role.as_str())?)
+                        .await?;
+                }
+            }
+
+            let invite = data
+                .invite_cache
+                .find_used(&ctx.http, new_member.guild_id)
+                .await;
+
+            MemberSnapshots::capture(data, [&new_member]).await?;
+
+            InviteJoins::record(
+                data,
+                new_member.user.id.0,
+                invite.as_ref().map(|x| x.code.clone()),
+                invite.as_ref().and_then(|x| x.inviter.map(|x| x.0)),
+            )
+            .await?;
 
-            let event = UserEvent::UserJoin(new_member.user.id);
+            let event = UserEvent::UserJoin(new_member.user.id, invite);
             event.post_to_log_channel(ctx, data).await?;
         }
         poise::Event::GuildMemberRemoval {
@@ -51,29 +92,20 @@ async fn event_handler(
             user,
             member_data_if_available,
         } => {
-            let cache = ctx.cache();
-
-            let event = match cache {
-                Some(cache) => {
-                    let all_roles = member_data_if_available.as_ref().unwrap();
-                    let all_roles = all_roles.roles(cache);
-
-                    if let Some(all_roles) = all_roles {
-                        UserEvent::UserLeave(user.id, all_roles)
-                    } else {
-                        UserEvent::UserLeave(user.id, vec![])
-                    }
-                }
-                None => UserEvent::UserLeave(user.id, vec![]),
-            };
+            // The cache misses members that were not seen since startup, the snapshot doesn't
+            let snapshot = MemberSnapshots::take(data, user.id).await?;
+            let departed = DepartedMember::new(member_data_if_available.as_ref(), snapshot);
 
+            let event = UserEvent::UserLeave(user.id, departed);
             event.post_to_log_channel(ctx, data).await?;
+
+            QuarantineMenu::member_left(ctx, data, user.id).await?;
         }
         poise::Event::GuildBanAddition {
-            guild_id: _,
+            guild_id,
             banned_user,
         } => {
-            let event = UserEvent::UserBan(banned_user.id);
+            let event = UserEvent::UserBan(*guild_id, banned_user.id);
             event.post_to_log_channel(ctx, data).await?;
         }
         poise::Event::GuildBanRemoval {
@@ -84,25 +116,158 @@ async fn event_handler(
             event.post_to_log_channel(ctx, data).await?;
         }
         poise::Event::VoiceStateUpdate { old, new } => {
+            if new.channel_id.is_some() {
+                MemberSnapshots::seen(data, new.user_id).await?;
+            }
+
             let voice_event = VoiceEvent::new(old, new);
             voice_event.post_to_log_channel(ctx, data).await?;
         }
         poise::Event::GuildMemberUpdate {
-            old_if_available,
+            old_if_available: Some(old),
             new,
         } => {
-            if let Some(old) = old_if_available {
-                let event = UserEvent::UserChange(new.user.id, UserChangeType::new(old, new));
+            MemberSnapshots::capture(data, [new]).await?;
+
+            let event =
+                UserEvent::UserChange(new.guild_id, new.user.id, UserChangeType::new(old, new));
+            event.post_to_log_channel(ctx, data).await?;
+        }
+        // Nothing to compare against, but the snapshot still has to follow the member
+        poise::Event::GuildMemberUpdate { new, .. } => {
+            MemberSnapshots::capture(data, [new]).await?;
+        }
+        poise::Event::PresenceUpdate { new_data } if new_data.user.bot != Some(true) => {
+            let Some(guild_id) = new_data.guild_id else {
+                return Ok(());
+            };
+
+            let playing = new_data
+                .activities
+                .iter()
+                .filter(|x| x.kind == ActivityType::Playing);
+
+            for activity in playing {
+                if GameActivity::played(data, new_data.user.id, &activity.name).await? {
+                    GameSuggestionMenu::suggest(
+                        ctx,
+                        data,
+                        guild_id,
+                        new_data.user.id,
+                        &activity.name,
+                    )
+                    .await?;
+                }
+            }
+        }
+        poise::Event::ThreadCreate { thread } => {
+            if let Some(event) = ThreadEvent::created(data, thread) {
+                event.post_to_log_channel(ctx, data).await?;
+            }
+        }
+        poise::Event::ThreadUpdate { thread } => {
+            let event = ThreadEvent::updated(data, thread.guild_id, thread);
+            event.post_to_log_channel(ctx, data).await?;
+        }
+        poise::Event::ThreadDelete { thread } => {
+            let event = ThreadEvent::deleted(data, thread);
+            event.post_to_log_channel(ctx, data).await?;
+        }
+        poise::Event::ThreadMembersUpdate {
+            thread_members_update,
+        } => {
+            let event = ThreadEvent::members_changed(thread_members_update);
+            event.post_to_log_channel(ctx, data).await?;
+        }
+        poise::Event::ThreadListSync { thread_list_sync } => {
+            data.thread_cache.extend(&thread_list_sync.threads);
+        }
+        poise::Event::GuildCreate { guild, .. } => {
+            data.thread_cache.extend(&guild.threads);
+            MemberSnapshots::capture(data, guild.members.values()).await?;
+        }
+        poise::Event::Message { new_message }
+            if new_message.guild_id.is_some() && !new_message.author.bot =>
+        {
+            MemberSnapshots::seen(data, new_message.author.id).await?;
+            data.message_cache
+                .insert(new_message.id, CachedMessage::from(new_message));
+        }
+        poise::Event::MessageUpdate { event, .. } => {
+            let event = MessageEvent::edited(data, event);
+            event.post_to_log_channel(ctx, data).await?;
+        }
+        poise::Event::MessageDelete {
+            channel_id,
+            deleted_message_id,
+            guild_id: Some(_),
+        } => {
+            let event = MessageEvent::deleted(data, *channel_id, *deleted_message_id);
+            event.post_to_log_channel(ctx, data).await?;
+        }
+        // Purges and bans with message deletion, logged like the same messages deleted one by one
+        poise::Event::MessageDeleteBulk {
+            channel_id,
+            multiple_deleted_messages_ids,
+            guild_id: Some(_),
+        } => {
+            for message_id in multiple_deleted_messages_ids {
+                let event = MessageEvent::deleted(data, *channel_id, *message_id);
                 event.post_to_log_channel(ctx, data).await?;
             }
         }
-        poise::Event::InteractionCreate { interaction } => match interaction {
-            Interaction::MessageComponent(message_component_interaction) => {
-                message_component_interactions::handle(ctx, message_component_interaction, data)
-                    .await?
+        poise::Event::ChannelCreate { channel } => {
+            GuildEvent::ChannelCreated(ChannelSnapshot::from(*channel))
+                .post_to_log_channel(data)?;
+        }
+        poise::Event::CategoryCreate { category } => {
+            GuildEvent::ChannelCreated(ChannelSnapshot::from(*category))
+                .post_to_log_channel(data)?;
+        }
+        poise::Event::ChannelUpdate { old, new } => {
+            if let Some(event) = GuildEvent::channel_updated(old, new) {
+                event.post_to_log_channel(data)?;
+            }
+        }
+        poise::Event::ChannelDelete { channel } => {
+            GuildEvent::ChannelDeleted(ChannelSnapshot::from(*channel))
+                .post_to_log_channel(data)?;
+        }
+        poise::Event::CategoryDelete { category } => {
+            GuildEvent::ChannelDeleted(ChannelSnapshot::from(*category))
+                .post_to_log_channel(data)?;
+        }
+        poise::Event::GuildRoleCreate { new } => {
+            GuildEvent::RoleCreated(new.clone()).post_to_log_channel(data)?;
+        }
+        poise::Event::GuildRoleUpdate {
+            old_data_if_available,
+            new,
+        } => {
+            if let Some(event) = GuildEvent::role_updated(old_data_if_available, new) {
+                event.post_to_log_channel(data)?;
             }
-            _ => (),
-        },
+        }
+        poise::Event::GuildRoleDelete {
+            removed_role_id,
+            removed_role_data_if_available,
+            ..
+        } => {
+            let event =
+                GuildEvent::RoleDeleted(*removed_role_id, removed_role_data_if_available.clone());
+            event.post_to_log_channel(data)?;
+        }
+        poise::Event::InviteCreate { data: invite } => {
+            data.invite_cache.created(invite);
+        }
+        poise::Event::InviteDelete { data: invite } => {
+            data.invite_cache.deleted(invite);
+        }
+        poise::Event::InteractionCreate {
+            interaction: Interaction::MessageComponent(message_component_interaction),
+        } => {
+            message_component_interactions::handle(ctx, message_component_interaction, data).await?
+        }
         _ => (),
     }
 
@@ -180,11 +345,32 @@ async fn poise(
     drop(t_roles);
     drop(t_rooms);
 
+    // Optional, defaults to shuttle_persist when not set
+    let bot_state = StateBackend::from_config(
+        secret_store.get("STATE_BACKEND"),
+        secret_store.get("STATE_DIR"),
+        persist,
+    )?;
+
+    // Optional, log events are only mirrored to the sinks that are set
+    let mut event_sinks: Vec<Box<dyn EventSink>> = vec![];
+    if let Some(dir) = secret_store.get("EVENT_LOG_DIR").filter(|x| !x.is_empty()) {
+        event_sinks.push(Box::new(JsonlSink::new(dir.into())));
+    }
+    if let Some(url) = secret_store
+        .get("EVENT_WEBHOOK_URL")
+        .filter(|x| !x.is_empty())
+    {
+        let sink = WebhookSink::new(url).context("Could not build the event webhook client")?;
+        event_sinks.push(Box::new(sink));
+    }
+
     let intents = GatewayIntents::GUILDS
         | GatewayIntents::GUILD_MESSAGES
         | GatewayIntents::MESSAGE_CONTENT
         | GatewayIntents::GUILD_VOICE_STATES
         | GatewayIntents::GUILD_BANS
+        | GatewayIntents::GUILD_INVITES
         | GatewayIntents::GUILD_PRESENCES
         | GatewayIntents::GUILD_MEMBERS;
 
@@ -204,16 +390,37 @@ async fn poise(
                 slash_commands::prune(),
                 slash_commands::pick_games_menu(),
                 slash_commands::unlock_triggered_channel(),
+                slash_commands::state_cache_stats(),
+                slash_commands::backup_state(),
+                slash_commands::restore_state(),
+                slash_commands::state_history(),
+                slash_commands::add_log_route(),
+                slash_commands::remove_log_route(),
+                slash_commands::reset_log_route(),
+                slash_commands::list_log_routes(),
+                slash_commands::voice_stats(),
+                slash_commands::invites(),
+                slash_commands::set_quarantine(),
+                slash_commands::disable_quarantine(),
+                slash_commands::history(),
+                slash_commands::game_suggestions(),
+                slash_commands::add_game_alias(),
+                slash_commands::remove_game_alias(),
+                slash_commands::game_activity_report(),
+                slash_commands::set_event_sink(),
+                slash_commands::list_event_sinks(),
                 // slash_commands::add_guild_application(),
                 // slash_commands::list_guild_application(),
                 // slash_commands::remove_guild_application(),
                 context_commands::archeage_apply(),
                 context_commands::triggered(),
                 context_commands::release_trigger(),
+                context_commands::user_history(),
             ],
             event_handler: |ctx, event, framework, data| {
                 Box::pin(event_handler(ctx, event, framework, data))
             },
+            on_error: |error| Box::pin(error::on_error(error)),
             ..Default::default()
         })
         .token(discord_token)
@@ -233,7 +440,8 @@ async fn poise(
                 poise::builtins::register_in_guild(ctx, &commands[1..], GuildId(guild_id)).await?;
 
                 let data = Data {
-                    bot_state: persist,
+                    bot_state,
+                    state_cache: Default::default(),
                     minor_events_channel,
                     major_events_channel,
                     follower_role,
@@ -242,8 +450,29 @@ async fn poise(
                     guild_apply_roles,
                     needs_to_apply_role,
                     needs_to_apply_channel,
+                    message_cache: Default::default(),
+                    log_dispatcher: LogDispatcher::start(ctx.http.clone()),
+                    invite_cache: Default::default(),
+                    event_sinks: EventSinks::start(event_sinks),
+                    thread_cache: Default::default(),
+                    pending_restore: Default::default(),
                 };
-                init_all_state(&data)?;
+                init_all_state(&data).await?;
+
+                // Joins can't be matched to invites until this succeeds, but the bot still works
+                if let Err(e) = data
+                    .invite_cache
+                    .refresh(&ctx.http, GuildId(guild_id))
+                    .await
+                {
+                    tracing::warn!("Could not load invites: {}", e);
+                }
+
+                let room_changes = TRooms::reconcile(&data).await?;
+                if !room_changes.is_empty() {
+                    let event = StartupEvent::TRoomsReconciled(room_changes);
+                    event.post_to_log_channel(ctx, &data).await?;
+                }
 
                 Ok(data)
             })
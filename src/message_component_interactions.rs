@@ -1,12 +1,17 @@
 use crate::data_enums::CustomId;
+use crate::state::active_collectors::{ActiveCollectors, SessionLookup};
 use crate::state::Data;
 use async_trait::async_trait;
 use poise::serenity_prelude::MessageComponentInteraction;
 use poise::serenity_prelude::{self as serenity};
 use std::{format, vec};
 
+pub mod game_suggestion_menu;
 mod guild_apply_menu;
+pub mod member_history_menu;
 mod pick_games_menu;
+pub mod quarantine_menu;
+mod state_restore_menu;
 
 pub async fn handle(
     ctx: &serenity::Context,
@@ -15,10 +20,51 @@ pub async fn handle(
 ) -> Result<(), crate::Error> {
     let custom_id = CustomId::new(&message_component_interaction.data.custom_id);
 
+    if custom_id.is_session_bound() {
+        let session = ActiveCollectors::find(
+            data,
+            message_component_interaction.message.id,
+            message_component_interaction.user.id,
+        )
+        .await?;
+
+        match session {
+            SessionLookup::Active => (),
+            SessionLookup::NotOwner => {
+                return reply_ephemeral(
+                    ctx,
+                    message_component_interaction,
+                    "This menu belongs to someone else...",
+                )
+                .await;
+            }
+            SessionLookup::Expired => {
+                return reply_ephemeral(
+                    ctx,
+                    message_component_interaction,
+                    "This menu has expired, please open it again...",
+                )
+                .await;
+            }
+            SessionLookup::NotFound => {
+                return reply_ephemeral(
+                    ctx,
+                    message_component_interaction,
+                    "This menu is no longer active, please open it again...",
+                )
+                .await;
+            }
+        }
+    }
+
     // List all interaction structs here
     let all_interactions = AllInteractions(vec![
         Box::new(pick_games_menu::PickGamesMenu::new(&custom_id).unwrap_or_default()),
         Box::new(guild_apply_menu::GuildApplyMenu::new(&custom_id).unwrap_or_default()),
+        Box::new(state_restore_menu::StateRestoreMenu::new(&custom_id).unwrap_or_default()),
+        Box::new(quarantine_menu::QuarantineMenu::new(&custom_id).unwrap_or_default()),
+        Box::new(member_history_menu::MemberHistoryMenu::new(&custom_id).unwrap_or_default()),
+        Box::new(game_suggestion_menu::GameSuggestionMenu::new(&custom_id).unwrap_or_default()),
     ]);
 
     for interaction in all_interactions.0 {
@@ -40,13 +86,23 @@ async fn invalid_interaction(
     ctx: &serenity::Context,
     message_component_interaction: &MessageComponentInteraction,
     custom_id: &CustomId,
+) -> Result<(), crate::Error> {
+    reply_ephemeral(
+        ctx,
+        message_component_interaction,
+        &format!("Invalid message component id: {}", custom_id),
+    )
+    .await
+}
+
+async fn reply_ephemeral(
+    ctx: &serenity::Context,
+    message_component_interaction: &MessageComponentInteraction,
+    content: &str,
 ) -> Result<(), crate::Error> {
     message_component_interaction
         .create_interaction_response(&ctx.http, |r| {
-            r.interaction_response_data(|f| {
-                f.ephemeral(true)
-                    .content(format!("Invalid message component id: {}", custom_id))
-            })
+            r.interaction_response_data(|f| f.ephemeral(true).content(content))
         })
         .await?;
     Ok(())
@@ -58,7 +114,7 @@ pub trait MsgComponentInteraction {
     where
         Self: Sized,
     {
-        if Self::valid_custom_ids().contains(&custom_id) {
+        if Self::valid_custom_ids().contains(custom_id) {
             Some(Self::inner_new(custom_id))
         } else {
             None
@@ -71,6 +127,8 @@ pub trait MsgComponentInteraction {
     fn valid_custom_ids() -> Vec<CustomId>
     where
         Self: Sized;
+    // Part of the menu contract, not every menu reads its id back yet
+    #[allow(dead_code)]
     fn custom_id(&self) -> &CustomId;
 
     async fn execute(
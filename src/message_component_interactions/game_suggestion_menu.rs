@@ -0,0 +1,214 @@
+use super::MsgComponentInteraction;
+use crate::data_enums::CustomId;
+use crate::state::active_collectors::{ActiveCollectors, Session, SessionKind};
+use crate::state::game_activity::GameActivity;
+use crate::state::games::Games;
+use crate::state::{Data, SnowflakeStorage, SnowflakesToRoles};
+use async_trait::async_trait;
+use poise::serenity_prelude::{self as serenity, ButtonStyle, GuildId, RoleId, UserId};
+use poise::serenity_prelude::{InteractionResponseType, MessageComponentInteraction};
+use std::vec;
+
+#[derive(Default)]
+pub struct GameSuggestionMenu(CustomId);
+
+#[async_trait]
+impl MsgComponentInteraction for GameSuggestionMenu {
+    // List all valid custom component ids here
+    fn valid_custom_ids() -> Vec<CustomId> {
+        vec![
+            CustomId::GameSuggestionAccept,
+            CustomId::GameSuggestionDecline,
+        ]
+    }
+
+    fn custom_id(&self) -> &CustomId {
+        &self.0
+    }
+
+    fn inner_new(custom_id: &CustomId) -> Self
+    where
+        Self: Sized,
+    {
+        Self(*custom_id)
+    }
+
+    // Match all valid custom component ids with their methods
+    async fn inner_execute(
+        &self,
+        ctx: &serenity::Context,
+        message_component_interaction: &MessageComponentInteraction,
+        data: &Data,
+    ) -> Result<bool, crate::Error> {
+        match self.0 {
+            CustomId::GameSuggestionAccept => {
+                Self::suggestion_accepted(ctx, message_component_interaction, data).await?;
+                return Ok(true);
+            }
+            CustomId::GameSuggestionDecline => {
+                Self::suggestion_declined(ctx, message_component_interaction, data).await?;
+                return Ok(true);
+            }
+            _ => (),
+        }
+
+        Ok(false)
+    }
+}
+
+// All component interaction methods defined here
+impl GameSuggestionMenu {
+    /// DMs an opted in member the game role of what they are playing, once per role
+    pub async fn suggest(
+        ctx: &serenity::Context,
+        data: &Data,
+        guild_id: GuildId,
+        user_id: UserId,
+        game_name: &str,
+    ) -> Result<(), crate::Error> {
+        let activity = GameActivity::load(data)?;
+        if !activity.opted_in.contains(&user_id.0) {
+            return Ok(());
+        }
+
+        let games = Games::load(data)?;
+        let Some(role_id) = activity.match_role(game_name, &games.to_roles(&ctx.cache)) else {
+            return Ok(());
+        };
+
+        // An alias can outlive the game it points to
+        if !games.snowflakes().contains(&role_id) {
+            return Ok(());
+        }
+
+        let Some(member) = ctx.cache.member(guild_id, user_id) else {
+            return Ok(());
+        };
+        if member.roles.contains(&role_id) {
+            return Ok(());
+        }
+
+        if !GameActivity::claim_suggestion(data, user_id, role_id).await? {
+            return Ok(());
+        }
+
+        let role_name = role_id
+            .to_role_cached(&ctx.cache)
+            .map(|x| x.name)
+            .unwrap_or(game_name.to_string());
+        let guild_name = guild_id
+            .name(&ctx.cache)
+            .unwrap_or("the server".to_string());
+
+        let channel = match user_id.create_dm_channel(&ctx.http).await {
+            Ok(channel) => channel,
+            Err(e) => {
+                tracing::warn!("Could not open a DM for a game suggestion: {}", e);
+                return Ok(());
+            }
+        };
+
+        let message = channel
+            .send_message(&ctx.http, |m| {
+                m.content(format!(
+                    "You play **{}**, want the **{}** role in {}?\nTurn these messages off with `/game_suggestions`",
+                    game_name, role_name, guild_name
+                ))
+                .components(|c| {
+                    c.create_action_row(|row| {
+                        row.create_button(|button| {
+                            button
+                                .custom_id(CustomId::GameSuggestionAccept.to_string())
+                                .label("Yes, add it")
+                                .style(ButtonStyle::Success)
+                        });
+                        row.create_button(|button| {
+                            button
+                                .custom_id(CustomId::GameSuggestionDecline.to_string())
+                                .label("No thanks")
+                                .style(ButtonStyle::Secondary)
+                        })
+                    })
+                })
+            })
+            .await;
+
+        let message = match message {
+            Ok(message) => message,
+            Err(e) => {
+                tracing::warn!("Could not send a game suggestion: {}", e);
+                return Ok(());
+            }
+        };
+
+        ActiveCollectors::start(
+            data,
+            user_id,
+            channel.id,
+            message.id,
+            SessionKind::GameSuggestion {
+                guild_id: guild_id.0,
+                role_id: role_id.0,
+            },
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn suggestion_accepted(
+        ctx: &serenity::Context,
+        message_component_interaction: &MessageComponentInteraction,
+        data: &Data,
+    ) -> Result<(), crate::Error> {
+        let session = ActiveCollectors::end(data, message_component_interaction.message.id).await?;
+
+        let content = match session {
+            Some(Session {
+                kind: SessionKind::GameSuggestion { guild_id, role_id },
+                ..
+            }) => {
+                let mut member = GuildId(guild_id)
+                    .member(&ctx.http, message_component_interaction.user.id)
+                    .await?;
+                member.add_role(&ctx.http, RoleId(role_id)).await?;
+
+                "Role added, have fun!".to_string()
+            }
+            _ => "This suggestion is no longer available...".to_string(),
+        };
+
+        Self::close_menu(ctx, message_component_interaction, content).await
+    }
+
+    pub async fn suggestion_declined(
+        ctx: &serenity::Context,
+        message_component_interaction: &MessageComponentInteraction,
+        data: &Data,
+    ) -> Result<(), crate::Error> {
+        ActiveCollectors::end(data, message_component_interaction.message.id).await?;
+
+        Self::close_menu(
+            ctx,
+            message_component_interaction,
+            "No problem, you won't be asked about this game again".to_string(),
+        )
+        .await
+    }
+
+    async fn close_menu(
+        ctx: &serenity::Context,
+        message_component_interaction: &MessageComponentInteraction,
+        content: String,
+    ) -> Result<(), crate::Error> {
+        message_component_interaction
+            .create_interaction_response(&ctx.http, |response| {
+                response
+                    .kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|m| m.content(content).components(|c| c))
+            })
+            .await?;
+
+        Ok(())
+    }
+}
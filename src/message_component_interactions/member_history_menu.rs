@@ -0,0 +1,220 @@
+use super::{reply_ephemeral, MsgComponentInteraction};
+use crate::data_enums::CustomId;
+use crate::extensions::InteractiveSnowflakeExt;
+use crate::state::active_collectors::{ActiveCollectors, Session, SessionKind};
+use crate::state::member_history::{MemberHistory, MemberHistoryEntry};
+use crate::state::Data;
+use crate::utils::embed::{truncate_to, DESCRIPTION_LIMIT};
+use crate::Context;
+use async_trait::async_trait;
+use poise::serenity_prelude::colours::branding::BLACK;
+use poise::serenity_prelude::{self as serenity, ButtonStyle, CreateComponents, CreateEmbed};
+use poise::serenity_prelude::{InteractionResponseType, MessageComponentInteraction, UserId};
+use std::vec;
+
+const PAGE_SIZE: usize = 10;
+
+#[derive(Default)]
+pub struct MemberHistoryMenu(CustomId);
+
+#[async_trait]
+impl MsgComponentInteraction for MemberHistoryMenu {
+    // List all valid custom component ids here
+    fn valid_custom_ids() -> Vec<CustomId> {
+        vec![CustomId::MemberHistoryNewer, CustomId::MemberHistoryOlder]
+    }
+
+    fn custom_id(&self) -> &CustomId {
+        &self.0
+    }
+
+    fn inner_new(custom_id: &CustomId) -> Self
+    where
+        Self: Sized,
+    {
+        Self(*custom_id)
+    }
+
+    // Match all valid custom component ids with their methods
+    async fn inner_execute(
+        &self,
+        ctx: &serenity::Context,
+        message_component_interaction: &MessageComponentInteraction,
+        data: &Data,
+    ) -> Result<bool, crate::Error> {
+        match self.0 {
+            CustomId::MemberHistoryNewer => {
+                Self::turn_page(ctx, message_component_interaction, data, -1).await?;
+                return Ok(true);
+            }
+            CustomId::MemberHistoryOlder => {
+                Self::turn_page(ctx, message_component_interaction, data, 1).await?;
+                return Ok(true);
+            }
+            _ => (),
+        }
+
+        Ok(false)
+    }
+}
+
+// All component interaction methods defined here
+impl MemberHistoryMenu {
+    /// Replies with the newest page of a member's timeline, shared by `/history` and the
+    /// "User History" context menu
+    pub async fn open(ctx: Context<'_>, user: &serenity::User) -> Result<(), crate::Error> {
+        let data = ctx.data();
+        let history = MemberHistory::load(data)?;
+        let entries = history.for_user(user.id);
+
+        if entries.is_empty() {
+            ctx.say(format!(
+                "No history recorded for {}",
+                user.id.get_interactive()
+            ))
+            .await?;
+            return Ok(());
+        }
+
+        let pages = Self::paginate(user.id, &entries);
+        let reply = ctx
+            .send(|b| {
+                b.embed(|e| Self::fill_page(e, user.id, &pages, 0, entries.len()))
+                    .components(|c| Self::add_buttons(c, 0, pages.len()))
+            })
+            .await?;
+
+        let message = reply.message().await?;
+        ActiveCollectors::start(
+            data,
+            ctx.author().id,
+            message.channel_id,
+            message.id,
+            SessionKind::MemberHistory {
+                user_id: user.id.0,
+                page: 0,
+            },
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn turn_page(
+        ctx: &serenity::Context,
+        message_component_interaction: &MessageComponentInteraction,
+        data: &Data,
+        step: isize,
+    ) -> Result<(), crate::Error> {
+        let message = &message_component_interaction.message;
+        let Some(Session {
+            kind: SessionKind::MemberHistory { user_id, page },
+            owner,
+            ..
+        }) = ActiveCollectors::get(data, message.id)?
+        else {
+            return reply_ephemeral(
+                ctx,
+                message_component_interaction,
+                "This menu has expired, please open it again...",
+            )
+            .await;
+        };
+
+        let history = MemberHistory::load(data)?;
+        let entries = history.for_user(user_id);
+        let pages = Self::paginate(UserId(user_id), &entries);
+        let page = page.saturating_add_signed(step).min(pages.len() - 1);
+
+        message_component_interaction
+            .create_interaction_response(&ctx.http, |response| {
+                response
+                    .kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|m| {
+                        m.embed(|e| {
+                            Self::fill_page(e, UserId(user_id), &pages, page, entries.len())
+                        })
+                        .components(|c| Self::add_buttons(c, page, pages.len()))
+                    })
+            })
+            .await?;
+
+        // Starting it again keeps the page and owner, and gives the pager a fresh lifetime
+        ActiveCollectors::start(
+            data,
+            owner,
+            message.channel_id,
+            message.id,
+            SessionKind::MemberHistory { user_id, page },
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    fn header(user_id: UserId) -> String {
+        format!("{}\n\n", user_id.get_interactive())
+    }
+
+    /// Pages end after `PAGE_SIZE` entries or before running past the description limit,
+    /// there is always at least one
+    fn paginate(user_id: UserId, entries: &[&MemberHistoryEntry]) -> Vec<String> {
+        let budget = DESCRIPTION_LIMIT - Self::header(user_id).chars().count();
+
+        let mut pages: Vec<String> = vec![String::new()];
+        for entry in entries.iter() {
+            let line = format!("{}\n", truncate_to(&entry.to_string(), budget - 1));
+            let current = pages.last_mut().expect("There is always a page");
+
+            let full = current.lines().count() >= PAGE_SIZE
+                || current.chars().count() + line.chars().count() > budget;
+            if full && !current.is_empty() {
+                pages.push(line);
+            } else {
+                current.push_str(&line);
+            }
+        }
+
+        pages
+    }
+
+    fn fill_page<'a>(
+        e: &'a mut CreateEmbed,
+        user_id: UserId,
+        pages: &[String],
+        page: usize,
+        events: usize,
+    ) -> &'a mut CreateEmbed {
+        e.title("Member History")
+            .color(BLACK)
+            .description(format!("{}{}", Self::header(user_id), pages[page]))
+            .footer(|f| {
+                f.text(format!(
+                    "Page {} of {}, {} event(s) | User ID: {}",
+                    page + 1,
+                    pages.len(),
+                    events,
+                    user_id
+                ))
+            })
+    }
+
+    fn add_buttons(c: &mut CreateComponents, page: usize, pages: usize) -> &mut CreateComponents {
+        c.create_action_row(|row| {
+            row.create_button(|button| {
+                button
+                    .custom_id(CustomId::MemberHistoryNewer.to_string())
+                    .label("Newer")
+                    .style(ButtonStyle::Secondary)
+                    .disabled(page == 0)
+            });
+            row.create_button(|button| {
+                button
+                    .custom_id(CustomId::MemberHistoryOlder.to_string())
+                    .label("Older")
+                    .style(ButtonStyle::Secondary)
+                    .disabled(page + 1 >= pages)
+            })
+        })
+    }
+}
@@ -1,6 +1,7 @@
 use super::MsgComponentInteraction;
 use crate::data_enums::CustomId;
 use crate::extensions::InteractiveSnowflakeExt;
+use crate::state::active_collectors::{ActiveCollectors, SessionKind};
 use crate::state::games::Games;
 use crate::state::{Data, SnowflakeStorage, SnowflakesToRoles};
 use crate::utils::discord_cdn::get_avatar_url;
@@ -91,45 +92,43 @@ impl PickGamesMenu {
                 .filter(|game| !user_roles.contains(&game.id))
                 .collect();
 
+            // The session has to exist before its dropdown can be clicked
+            Self::start_session(ctx, message_component_interaction, data).await?;
+
             message_component_interaction
-                .create_interaction_response(&ctx.http, |response| {
-                    response
-                        .kind(InteractionResponseType::ChannelMessageWithSource)
-                        .interaction_response_data(|message| {
-                            message
-                                .content("Please select the games you're interested in")
-                                .ephemeral(true)
-                                .components(|components| {
-                                    components.create_action_row(|row| {
-                                        // An action row can only contain one select menu!
-                                        row.create_select_menu(|menu| {
-                                            menu.custom_id(CustomId::PickGamesAddExecute.to_string());
-                                            menu.placeholder("No games selected");
-                                            if games.len() > 0 {
-                                                menu.max_values(
-                                                    u64::try_from(games.len())
-                                                        .expect("usize to u 64 conversion failed"),
-                                                );
-                                                menu.options(move |menu_options| {
-                                                    for game in games {
-                                                        menu_options.create_option(|option| {
-                                                            option.label(game.name).value(game.id)
-                                                        });
-                                                    }
-                                                    menu_options
-                                                })
-                                            } else {
-                                                menu.max_values(1);
-                                                menu.options(move |menu_options| {
-                                                    menu_options.create_option(|option| {
-                                                        option.label("All available games are already assigned to you...").value("__invalid__")
-                                                    });
-                                                    menu_options
-                                                })
+                .edit_original_interaction_response(&ctx.http, |message| {
+                    message
+                        .content("Please select the games you're interested in")
+                        .components(|components| {
+                            components.create_action_row(|row| {
+                                // An action row can only contain one select menu!
+                                row.create_select_menu(|menu| {
+                                    menu.custom_id(CustomId::PickGamesAddExecute.to_string());
+                                    menu.placeholder("No games selected");
+                                    if !games.is_empty() {
+                                        menu.max_values(
+                                            u64::try_from(games.len())
+                                                .expect("usize to u 64 conversion failed"),
+                                        );
+                                        menu.options(move |menu_options| {
+                                            for game in games {
+                                                menu_options.create_option(|option| {
+                                                    option.label(game.name).value(game.id)
+                                                });
                                             }
+                                            menu_options
+                                        })
+                                    } else {
+                                        menu.max_values(1);
+                                        menu.options(move |menu_options| {
+                                            menu_options.create_option(|option| {
+                                                option.label("All available games are already assigned to you...").value("__invalid__")
+                                            });
+                                            menu_options
                                         })
-                                    })
+                                    }
                                 })
+                            })
                         })
                 })
                 .await?;
@@ -153,17 +152,17 @@ impl PickGamesMenu {
 
         if let Some(member) = user.as_mut() {
             let selected_games: Vec<RoleId> = selected_games
-                .into_iter()
+                .iter()
                 .map(|x| {
                     RoleId(
                         x.parse::<u64>()
                             .expect("Failed to parse String into u64..."),
                     )
                 })
-                .filter(|game| !&member.roles.contains(&game))
+                .filter(|game| !&member.roles.contains(game))
                 .collect();
 
-            if selected_games.len() < 1 {
+            if selected_games.is_empty() {
                 invalid_execution = true;
             }
 
@@ -177,9 +176,8 @@ impl PickGamesMenu {
                 .filter(|x| data.guild_apply_roles.contains(*x))
                 .collect();
 
-            if guild_apply_roles.len() > 0 {
-                let needs_to_apply_role = RoleId::from_str(&data.needs_to_apply_role)
-                    .expect("NEEDS_TO_APPLY_ROLE is not valid");
+            if !guild_apply_roles.is_empty() {
+                let needs_to_apply_role = RoleId::from_str(&data.needs_to_apply_role)?;
                 member.add_role(&ctx.http, needs_to_apply_role).await?;
                 member
                     .user
@@ -220,11 +218,7 @@ impl PickGamesMenu {
                                     .field("New Roles: ", display_roles, true)
                                     .timestamp(Utc::now())
                                     .set_author(author)
-                                    .field(
-                                        "Display Name",
-                                        format!("{}", member.user.id.get_interactive(),),
-                                        false,
-                                    )
+                                    .field("Display Name", member.user.id.get_interactive(), false)
                                     .set_footer(footer);
 
                                 let mut e2 = CreateEmbed::default();
@@ -237,7 +231,7 @@ impl PickGamesMenu {
                                     ))
                                     .color(RED);
 
-                                if guild_apply_roles.len() > 0 {
+                                if !guild_apply_roles.is_empty() {
                                     m.add_embed(e2)
                                 } else {
                                     m.add_embed(e1)
@@ -270,45 +264,43 @@ impl PickGamesMenu {
                 .filter(|game| user_roles.contains(&game.id))
                 .collect();
 
+            // The session has to exist before its dropdown can be clicked
+            Self::start_session(ctx, message_component_interaction, data).await?;
+
             message_component_interaction
-                .create_interaction_response(&ctx.http, |response| {
-                    response
-                        .kind(InteractionResponseType::ChannelMessageWithSource)
-                        .interaction_response_data(|message| {
-                            message
-                                .content("Please select the game roles you would like to remove")
-                                .ephemeral(true)
-                                .components(|components| {
-                                    components.create_action_row(|row| {
-                                        // An action row can only contain one select menu!
-                                        row.create_select_menu(|menu| {
-                                            menu.custom_id(CustomId::PickGamesRemoveExecute.to_string());
-                                            menu.placeholder("No games selected");
-                                            if games.len() > 0 {
-                                                menu.max_values(
-                                                    u64::try_from(games.len())
-                                                        .expect("usize to u 64 conversion failed"),
-                                                );
-                                                menu.options(move |menu_options| {
-                                                    for game in games {
-                                                        menu_options.create_option(|option| {
-                                                            option.label(game.name).value(game.id)
-                                                        });
-                                                    }
-                                                    menu_options
-                                                })
-                                            } else {
-                                                menu.max_values(1);
-                                                menu.options(move |menu_options| {
-                                                    menu_options.create_option(|option| {
-                                                        option.label("None of the available games are assigned to you...").value("__invalid__")
-                                                    });
-                                                    menu_options
-                                                })
+                .edit_original_interaction_response(&ctx.http, |message| {
+                    message
+                        .content("Please select the game roles you would like to remove")
+                        .components(|components| {
+                            components.create_action_row(|row| {
+                                // An action row can only contain one select menu!
+                                row.create_select_menu(|menu| {
+                                    menu.custom_id(CustomId::PickGamesRemoveExecute.to_string());
+                                    menu.placeholder("No games selected");
+                                    if !games.is_empty() {
+                                        menu.max_values(
+                                            u64::try_from(games.len())
+                                                .expect("usize to u 64 conversion failed"),
+                                        );
+                                        menu.options(move |menu_options| {
+                                            for game in games {
+                                                menu_options.create_option(|option| {
+                                                    option.label(game.name).value(game.id)
+                                                });
                                             }
+                                            menu_options
                                         })
-                                    })
+                                    } else {
+                                        menu.max_values(1);
+                                        menu.options(move |menu_options| {
+                                            menu_options.create_option(|option| {
+                                                option.label("None of the available games are assigned to you...").value("__invalid__")
+                                            });
+                                            menu_options
+                                        })
+                                    }
                                 })
+                            })
                         })
                 })
                 .await?;
@@ -333,17 +325,17 @@ impl PickGamesMenu {
 
         if let Some(member) = user.as_mut() {
             let selected_games: Vec<RoleId> = selected_games
-                .into_iter()
+                .iter()
                 .map(|x| {
                     RoleId(
                         x.parse::<u64>()
                             .expect("Failed to parse String into u64..."),
                     )
                 })
-                .filter(|game| member.roles.contains(&game))
+                .filter(|game| member.roles.contains(game))
                 .collect();
 
-            if selected_games.len() < 1 {
+            if selected_games.is_empty() {
                 invalid_execution = true;
             }
 
@@ -376,7 +368,7 @@ impl PickGamesMenu {
                                         .set_author(author)
                                         .field(
                                             "Display Name",
-                                            format!("{}", member.user.id.get_interactive(),),
+                                            member.user.id.get_interactive(),
                                             false,
                                         )
                                         .set_footer(footer)
@@ -391,4 +383,34 @@ impl PickGamesMenu {
 
         Ok(())
     }
+
+    /// Answers with an empty ephemeral message and registers it, the dropdown is edited in after
+    async fn start_session(
+        ctx: &serenity::Context,
+        message_component_interaction: &MessageComponentInteraction,
+        data: &Data,
+    ) -> Result<(), crate::Error> {
+        message_component_interaction
+            .create_interaction_response(&ctx.http, |response| {
+                response
+                    .kind(InteractionResponseType::DeferredChannelMessageWithSource)
+                    .interaction_response_data(|message| message.ephemeral(true))
+            })
+            .await?;
+
+        let message = message_component_interaction
+            .get_interaction_response(&ctx.http)
+            .await?;
+
+        ActiveCollectors::start(
+            data,
+            message_component_interaction.user.id,
+            message.channel_id,
+            message.id,
+            SessionKind::PickGames,
+        )
+        .await?;
+
+        Ok(())
+    }
 }
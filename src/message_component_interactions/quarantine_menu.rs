@@ -0,0 +1,271 @@
+use super::{reply_ephemeral, MsgComponentInteraction};
+use crate::data_enums::CustomId;
+use crate::error::{channel_from_secret, BotError};
+use crate::extensions::InteractiveSnowflakeExt;
+use crate::state::quarantine::{Quarantine, Quarantined};
+use crate::state::Data;
+use crate::utils::{discord_cdn::get_avatar_url, time::date_diff};
+use async_trait::async_trait;
+use chrono::Utc;
+use poise::serenity_prelude::colours::branding::RED;
+use poise::serenity_prelude::{self as serenity, ButtonStyle, Member, Permissions, RoleId, UserId};
+use poise::serenity_prelude::{InteractionResponseType, MessageComponentInteraction};
+use std::str::FromStr;
+use std::vec;
+
+#[derive(Default)]
+pub struct QuarantineMenu(CustomId);
+
+#[async_trait]
+impl MsgComponentInteraction for QuarantineMenu {
+    // List all valid custom component ids here
+    fn valid_custom_ids() -> Vec<CustomId> {
+        vec![
+            CustomId::QuarantineApprove,
+            CustomId::QuarantineKick,
+            CustomId::QuarantineBan,
+        ]
+    }
+
+    fn custom_id(&self) -> &CustomId {
+        &self.0
+    }
+
+    fn inner_new(custom_id: &CustomId) -> Self
+    where
+        Self: Sized,
+    {
+        Self(*custom_id)
+    }
+
+    // Match all valid custom component ids with their methods
+    async fn inner_execute(
+        &self,
+        ctx: &serenity::Context,
+        message_component_interaction: &MessageComponentInteraction,
+        data: &Data,
+    ) -> Result<bool, crate::Error> {
+        match self.0 {
+            CustomId::QuarantineApprove | CustomId::QuarantineKick | CustomId::QuarantineBan => {
+                Self::resolve(ctx, message_component_interaction, data, self.0).await?;
+                return Ok(true);
+            }
+            _ => (),
+        }
+
+        Ok(false)
+    }
+}
+
+// All component interaction methods defined here
+impl QuarantineMenu {
+    /// Posts the moderator alert for a member that was just quarantined.
+    /// Not routable, somebody has to act on it
+    pub async fn post_alert(
+        ctx: &serenity::Context,
+        data: &Data,
+        member: &Member,
+        role_id: RoleId,
+    ) -> Result<(), crate::Error> {
+        let user = &member.user;
+        let target_channel =
+            channel_from_secret(&data.major_events_channel, "MAJOR_EVENTS_CHANNEL")?;
+
+        let message = target_channel
+            .send_message(&ctx.http, |m| {
+                m.embed(|e| {
+                    e.title("New Account Quarantined")
+                        .color(RED)
+                        .description(format!(
+                            "{} was given {} instead of the follower role",
+                            user.id.get_interactive(),
+                            role_id.get_interactive()
+                        ))
+                        .author(|a| a.icon_url(get_avatar_url(user)).name(&user.name))
+                        .field("Account Age", date_diff(&user.created_at()), true)
+                        .field(
+                            "Account Created",
+                            format!("<t:{}:R>", user.created_at().unix_timestamp()),
+                            true,
+                        )
+                        .timestamp(Utc::now())
+                        .footer(|f| f.text(format!("User ID: {}", user.id)))
+                })
+                .components(|c| {
+                    c.create_action_row(|row| {
+                        row.create_button(|button| {
+                            button
+                                .custom_id(CustomId::QuarantineApprove.to_string())
+                                .label("Approve")
+                                .style(ButtonStyle::Success)
+                        });
+                        row.create_button(|button| {
+                            button
+                                .custom_id(CustomId::QuarantineKick.to_string())
+                                .label("Kick")
+                                .style(ButtonStyle::Secondary)
+                        });
+                        row.create_button(|button| {
+                            button
+                                .custom_id(CustomId::QuarantineBan.to_string())
+                                .label("Ban")
+                                .style(ButtonStyle::Danger)
+                        })
+                    })
+                })
+            })
+            .await?;
+
+        Quarantine::add_pending(data, message.id, user.id, role_id).await?;
+
+        Ok(())
+    }
+
+    /// Closes the alerts of a member that left before a moderator got to them
+    pub async fn member_left(
+        ctx: &serenity::Context,
+        data: &Data,
+        user_id: UserId,
+    ) -> Result<(), crate::Error> {
+        let alerts = Quarantine::take_pending_for_user(data, user_id).await?;
+        if alerts.is_empty() {
+            return Ok(());
+        }
+
+        let target_channel =
+            channel_from_secret(&data.major_events_channel, "MAJOR_EVENTS_CHANNEL")?;
+
+        for alert in alerts {
+            let result = target_channel
+                .edit_message(&ctx.http, alert.message_id, |m| {
+                    m.content("Left the server before being handled")
+                        .components(|c| c)
+                })
+                .await;
+
+            if let Err(e) = result {
+                tracing::warn!("Could not close the quarantine alert: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn resolve(
+        ctx: &serenity::Context,
+        message_component_interaction: &MessageComponentInteraction,
+        data: &Data,
+        action: CustomId,
+    ) -> Result<(), crate::Error> {
+        let (required, permission_name) = match action {
+            CustomId::QuarantineApprove => (Permissions::MANAGE_ROLES, "Manage Roles"),
+            CustomId::QuarantineKick => (Permissions::KICK_MEMBERS, "Kick Members"),
+            _ => (Permissions::BAN_MEMBERS, "Ban Members"),
+        };
+
+        let permissions = message_component_interaction
+            .member
+            .as_ref()
+            .and_then(|x| x.permissions)
+            .unwrap_or_default();
+
+        if !permissions.contains(required) {
+            return reply_ephemeral(
+                ctx,
+                message_component_interaction,
+                &format!("You need the {} permission to do this...", permission_name),
+            )
+            .await;
+        }
+
+        let message_id = message_component_interaction.message.id;
+        let Some(quarantined) = Quarantine::take_pending(data, message_id).await? else {
+            return reply_ephemeral(
+                ctx,
+                message_component_interaction,
+                "This member was already handled...",
+            )
+            .await;
+        };
+
+        let moderator = &message_component_interaction.user;
+        let result = Self::apply(
+            ctx,
+            message_component_interaction,
+            data,
+            action,
+            &quarantined,
+        )
+        .await;
+
+        let outcome = match result {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                // Keep the buttons working, so the action can be retried
+                Quarantine::add_pending(data, message_id, quarantined.user_id, quarantined.role_id)
+                    .await?;
+                return Err(e);
+            }
+        };
+
+        message_component_interaction
+            .create_interaction_response(&ctx.http, |response| {
+                response
+                    .kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|m| {
+                        m.content(format!("{} by {}", outcome, moderator.id.get_interactive()))
+                            .components(|c| c)
+                    })
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Carries out the moderator's decision, returning what happened for the alert
+    async fn apply(
+        ctx: &serenity::Context,
+        message_component_interaction: &MessageComponentInteraction,
+        data: &Data,
+        action: CustomId,
+        quarantined: &Quarantined,
+    ) -> Result<&'static str, crate::Error> {
+        let guild_id = message_component_interaction
+            .guild_id
+            .ok_or_else(|| BotError::User("Quarantine alerts only work in a server...".into()))?;
+
+        let user_id = serenity::UserId(quarantined.user_id);
+        let reason = format!(
+            "Quarantined new account, handled by {}",
+            message_component_interaction.user.name
+        );
+
+        match action {
+            CustomId::QuarantineApprove => {
+                let mut member = guild_id.member(&ctx.http, user_id).await?;
+                member
+                    .remove_role(&ctx.http, RoleId(quarantined.role_id))
+                    .await?;
+                member
+                    .add_role(&ctx.http, RoleId::from_str(&data.follower_role)?)
+                    .await?;
+
+                Ok("Approved")
+            }
+            CustomId::QuarantineKick => {
+                guild_id
+                    .kick_with_reason(&ctx.http, user_id, &reason)
+                    .await?;
+
+                Ok("Kicked")
+            }
+            _ => {
+                guild_id
+                    .ban_with_reason(&ctx.http, user_id, 0, &reason)
+                    .await?;
+
+                Ok("Banned")
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use super::MsgComponentInteraction;
+use crate::data_enums::CustomId;
+use crate::state::active_collectors::{ActiveCollectors, Session, SessionKind};
+use crate::state::Data;
+use async_trait::async_trait;
+use poise::serenity_prelude::{self as serenity};
+use poise::serenity_prelude::{InteractionResponseType, MessageComponentInteraction};
+use std::vec;
+
+#[derive(Default)]
+pub struct StateRestoreMenu(CustomId);
+
+#[async_trait]
+impl MsgComponentInteraction for StateRestoreMenu {
+    // List all valid custom component ids here
+    fn valid_custom_ids() -> Vec<CustomId> {
+        vec![CustomId::StateRestoreConfirm, CustomId::StateRestoreCancel]
+    }
+
+    fn custom_id(&self) -> &CustomId {
+        &self.0
+    }
+
+    fn inner_new(custom_id: &CustomId) -> Self
+    where
+        Self: Sized,
+    {
+        Self(*custom_id)
+    }
+
+    // Match all valid custom component ids with their methods
+    async fn inner_execute(
+        &self,
+        ctx: &serenity::Context,
+        message_component_interaction: &MessageComponentInteraction,
+        data: &Data,
+    ) -> Result<bool, crate::Error> {
+        match self.0 {
+            CustomId::StateRestoreConfirm => {
+                Self::restore_confirmed(ctx, message_component_interaction, data).await?;
+                return Ok(true);
+            }
+            CustomId::StateRestoreCancel => {
+                Self::restore_cancelled(ctx, message_component_interaction, data).await?;
+                return Ok(true);
+            }
+            _ => (),
+        }
+
+        Ok(false)
+    }
+}
+
+// All component interaction methods defined here
+impl StateRestoreMenu {
+    pub async fn restore_confirmed(
+        ctx: &serenity::Context,
+        message_component_interaction: &MessageComponentInteraction,
+        data: &Data,
+    ) -> Result<(), crate::Error> {
+        let message_id = message_component_interaction.message.id;
+        let session = ActiveCollectors::end(data, message_id).await?;
+        let backup = data.pending_restore.take(message_id);
+
+        let content = match (session, backup) {
+            (
+                Some(Session {
+                    kind: SessionKind::StateRestore,
+                    ..
+                }),
+                Some(backup),
+            ) => {
+                backup.restore(data).await?;
+                format!(
+                    "State restored from the backup created at {}",
+                    backup.created_at
+                )
+            }
+            _ => "No pending restore found, run `/restore_state` again...".to_string(),
+        };
+
+        Self::close_menu(ctx, message_component_interaction, content).await
+    }
+
+    pub async fn restore_cancelled(
+        ctx: &serenity::Context,
+        message_component_interaction: &MessageComponentInteraction,
+        data: &Data,
+    ) -> Result<(), crate::Error> {
+        let message_id = message_component_interaction.message.id;
+        ActiveCollectors::end(data, message_id).await?;
+        data.pending_restore.take(message_id);
+
+        Self::close_menu(
+            ctx,
+            message_component_interaction,
+            "Restore cancelled, nothing was written".to_string(),
+        )
+        .await
+    }
+
+    async fn close_menu(
+        ctx: &serenity::Context,
+        message_component_interaction: &MessageComponentInteraction,
+        content: String,
+    ) -> Result<(), crate::Error> {
+        message_component_interaction
+            .create_interaction_response(&ctx.http, |response| {
+                response
+                    .kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|m| m.content(content).components(|c| c))
+            })
+            .await?;
+
+        Ok(())
+    }
+}
@@ -1,20 +1,39 @@
 use crate::checks::is_on_admin_list;
 use crate::constants::MASTER_ADMIN;
 use crate::data_enums::CustomId;
+use crate::error::BotError;
+use crate::extensions::InteractiveSnowflakeExt;
+use crate::message_component_interactions::member_history_menu::MemberHistoryMenu;
+use crate::state::active_collectors::{ActiveCollectors, SessionKind};
 use crate::state::admins::Admins;
+use crate::state::backup::StateBackup;
+use crate::state::game_activity::GameActivity;
 use crate::state::games::Games;
 use crate::state::guild_apply::GuildApply;
+use crate::state::history::StateHistory;
+use crate::state::invite_joins::InviteJoins;
+use crate::state::log_routes::{LogKind, LogRoutes};
+use crate::state::migrations;
+use crate::state::quarantine::{Quarantine, QuarantineSettings};
+use crate::state::sink_filters::{SinkFilters, SinkKind};
 use crate::state::t_rooms::TRooms;
+use crate::state::voice_stats::{day_of, oldest_day, StatsFilter, VoiceStats};
+use crate::state::BotStateInitialization;
 use crate::state::SnowflakeHashmapStorage;
 use crate::state::SnowflakeStorage;
+use crate::state::SnowflakesToRoles;
+use crate::utils::embed::DESCRIPTION_LIMIT;
+use crate::utils::time::format_duration;
 use crate::Context;
 use crate::Error;
+use chrono::{NaiveDate, NaiveTime, Utc};
 use poise::futures_util::StreamExt;
 use poise::serenity_prelude::colours::branding::BLACK;
-use poise::serenity_prelude::ButtonStyle;
 use poise::serenity_prelude::{self as serenity};
-use poise::serenity_prelude::{CacheHttp, MessageId};
+use poise::serenity_prelude::{AttachmentType, ButtonStyle};
+use poise::serenity_prelude::{CacheHttp, ChannelId, MessageId, UserId};
 use std::format;
+use std::str::FromStr;
 
 /// Check if bot is online
 #[poise::command(slash_command, ephemeral)]
@@ -29,11 +48,9 @@ pub async fn add_admin(
     ctx: Context<'_>,
     #[description = "Selected user"] user: serenity::User,
 ) -> Result<(), Error> {
-    let user_id: u64 = user.id.into();
     let data = ctx.data();
 
-    let mut admins = Admins::load(data)?;
-    let successful = admins.add(data, user_id)?;
+    let successful = Admins::add(data, &ctx.into(), user.id).await?;
 
     if successful {
         ctx.say("User was added to the Admin list!").await?;
@@ -50,17 +67,14 @@ pub async fn remove_admin(
     ctx: Context<'_>,
     #[description = "Selected user"] user: serenity::User,
 ) -> Result<(), Error> {
-    let user_id: u64 = user.id.into();
-
-    if user_id == MASTER_ADMIN {
+    if user.id.0 == MASTER_ADMIN {
         ctx.say("Can not remove master admin!").await?;
         return Ok(());
     }
 
     let data = ctx.data();
 
-    let mut admins = Admins::load(data)?;
-    let successful = admins.remove(data, user_id)?;
+    let successful = Admins::remove(data, &ctx.into(), user.id).await?;
 
     if successful {
         ctx.say("User was remove from the Admin list!").await?;
@@ -94,11 +108,9 @@ pub async fn add_game(
     ctx: Context<'_>,
     #[description = "Selected user"] role: serenity::Role,
 ) -> Result<(), Error> {
-    let role_id: u64 = role.id.into();
     let data = ctx.data();
 
-    let mut games = Games::load(data)?;
-    let successful = games.add(data, role_id)?;
+    let successful = Games::add(data, &ctx.into(), role.id).await?;
 
     if successful {
         ctx.say("Game was added to the game list!").await?;
@@ -115,12 +127,9 @@ pub async fn remove_game(
     ctx: Context<'_>,
     #[description = "Selected user"] role: serenity::Role,
 ) -> Result<(), Error> {
-    let role_id: u64 = role.id.into();
     let data = ctx.data();
 
-    let mut games = Games::load(data)?;
-
-    let successful = games.remove(data, role_id)?;
+    let successful = Games::remove(data, &ctx.into(), role.id).await?;
 
     if successful {
         ctx.say("Game was remove from the games list!").await?;
@@ -132,6 +141,125 @@ pub async fn remove_game(
     Ok(())
 }
 
+/// Get a DM offering the game role when you play a registered game
+#[poise::command(slash_command, ephemeral)]
+pub async fn game_suggestions(
+    ctx: Context<'_>,
+    #[description = "Send me game role suggestions"] enabled: bool,
+) -> Result<(), Error> {
+    let changed = GameActivity::set_opt_in(ctx.data(), ctx.author().id, enabled).await?;
+
+    match (changed, enabled) {
+        (true, true) => {
+            ctx.say("You'll get a DM when you play a game that has a role")
+                .await?
+        }
+        (true, false) => {
+            ctx.say("You'll no longer get game role suggestions")
+                .await?
+        }
+        (false, true) => ctx.say("Game role suggestions are already on...").await?,
+        (false, false) => ctx.say("Game role suggestions are already off...").await?,
+    };
+
+    Ok(())
+}
+
+/// Match a differently named game activity to a game role
+#[poise::command(slash_command, ephemeral, required_permissions = "ADMINISTRATOR")]
+pub async fn add_game_alias(
+    ctx: Context<'_>,
+    #[description = "Game name as shown in Discord activities"] alias: String,
+    #[description = "Game role"] role: serenity::Role,
+) -> Result<(), Error> {
+    let data = ctx.data();
+
+    if !Games::load(data)?.snowflakes().contains(&role.id) {
+        return Err(BotError::User(format!(
+            "{} is not on the game list, add it with `/add_game` first...",
+            role.id.get_interactive()
+        )));
+    }
+
+    let successful = GameActivity::add_alias(data, &ctx.into(), &alias, role.id).await?;
+
+    if successful {
+        ctx.say(format!(
+            "Playing `{}` now counts as {}",
+            alias,
+            role.id.get_interactive()
+        ))
+        .await?;
+    } else {
+        ctx.say("Alias is already registered...").await?;
+    }
+
+    Ok(())
+}
+
+/// Stop matching a game activity alias
+#[poise::command(slash_command, ephemeral, required_permissions = "ADMINISTRATOR")]
+pub async fn remove_game_alias(
+    ctx: Context<'_>,
+    #[description = "Game name as shown in Discord activities"] alias: String,
+) -> Result<(), Error> {
+    let successful = GameActivity::remove_alias(ctx.data(), &ctx.into(), &alias).await?;
+
+    if successful {
+        ctx.say("Alias was removed!").await?;
+    } else {
+        ctx.say("Alias could not be found...").await?;
+    }
+
+    Ok(())
+}
+
+const GAME_REPORT_TOP: usize = 15;
+
+/// Display the most played games that have no game role or alias yet
+#[poise::command(slash_command, ephemeral, required_permissions = "ADMINISTRATOR")]
+pub async fn game_activity_report(ctx: Context<'_>) -> Result<(), Error> {
+    let data = ctx.data();
+    let activity = GameActivity::load(data)?;
+    let games = Games::load(data)?.to_roles(ctx.serenity_context().cache.as_ref());
+
+    let lines: Vec<String> = activity
+        .unregistered(&games)
+        .iter()
+        .take(GAME_REPORT_TOP)
+        .enumerate()
+        .map(|(i, game)| {
+            format!(
+                "{}. **{}** {} player(s), last played <t:{}:R>",
+                i + 1,
+                game.name,
+                game.players.len(),
+                game.last_played
+            )
+        })
+        .collect();
+
+    if lines.is_empty() {
+        ctx.say("Every played game already has a role").await?;
+        return Ok(());
+    }
+
+    let aliases: Vec<String> = activity
+        .aliases
+        .iter()
+        .map(|(alias, role_id)| format!("`{}` -> <@&{}>", alias, role_id))
+        .collect();
+
+    let mut out = format!("**Unregistered games**\n{}", lines.join("\n"));
+    if !aliases.is_empty() {
+        out.push_str(&format!("\n\n**Aliases**\n{}", aliases.join("\n")));
+    }
+
+    ctx.say(out).await?;
+
+    Ok(())
+}
+
 /// Display game list
 #[poise::command(slash_command, ephemeral, required_permissions = "ADMINISTRATOR")]
 pub async fn list_games(ctx: Context<'_>) -> Result<(), Error> {
@@ -242,16 +370,20 @@ pub async fn unlock_triggered_channel(ctx: Context<'_>) -> Result<(), Error> {
     }
 
     // Find Room struct using target_channel, then toggle the lock
-    let mut t_rooms = TRooms::load(ctx.data())?;
-    let room = t_rooms.find_room(target_channel)?;
+    let data = ctx.data();
+    let is_t_room = TRooms::load(data)?.find_room(target_channel)?.is_some();
 
-    if let Some(&mut ref mut room) = room {
+    if is_t_room {
         if !message_ids.is_empty() {
             target_channel.delete_messages(http, message_ids).await?;
         }
-        room.toggle_open();
-        let data = ctx.data();
-        t_rooms.save(data)?;
+
+        TRooms::update(data, |t_rooms| {
+            if let Ok(Some(room)) = t_rooms.find_room(target_channel) {
+                room.toggle_open();
+            }
+        })
+        .await?;
 
         ctx.say("Room unlocked!!").await?;
     } else {
@@ -261,6 +393,573 @@ pub async fn unlock_triggered_channel(ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
+/// Display how often each state key was served from the in-memory cache
+#[poise::command(slash_command, ephemeral, required_permissions = "ADMINISTRATOR")]
+pub async fn state_cache_stats(ctx: Context<'_>) -> Result<(), Error> {
+    let stats = ctx.data().state_cache.stats();
+
+    if stats.is_empty() {
+        ctx.say("No state has been read since startup").await?;
+        return Ok(());
+    }
+
+    let mut out = String::new();
+    for stat in stats {
+        let total = stat.hits + stat.misses;
+        let hit_rate = (stat.hits * 100).checked_div(total).unwrap_or(0);
+
+        out.push_str(&format!(
+            "`{}`: {} hits, {} misses ({}% hit rate)\n",
+            stat.key, stat.hits, stat.misses, hit_rate
+        ));
+    }
+
+    ctx.say(out).await?;
+
+    Ok(())
+}
+
+/// Download every persisted state key as one JSON backup file
+#[poise::command(slash_command, ephemeral, owners_only)]
+pub async fn backup_state(ctx: Context<'_>) -> Result<(), Error> {
+    let backup = StateBackup::create(ctx.data())?;
+    let bytes = backup.to_bytes()?;
+    let filename = format!(
+        "mythicc-state-{}.json",
+        Utc::now().format("%Y-%m-%d-%H%M%S")
+    );
+
+    ctx.send(|b| {
+        b.content(format!("State backup of {} key(s)", backup.keys.len()))
+            .attachment(AttachmentType::Bytes {
+                data: bytes.into(),
+                filename,
+            })
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Restore persisted state from a `/backup_state` file, shows a dry run first
+#[poise::command(slash_command, ephemeral, owners_only)]
+pub async fn restore_state(
+    ctx: Context<'_>,
+    #[description = "Backup file created by /backup_state"] backup: serenity::Attachment,
+) -> Result<(), Error> {
+    let data = ctx.data();
+
+    let bytes = backup.download().await?;
+    let backup = StateBackup::from_bytes(&bytes)
+        .map_err(|e| BotError::User(format!("Backup rejected: {}", e)))?;
+
+    let diff = backup.diff(data)?.join("\n");
+    let created_at = backup.created_at.clone();
+
+    let reply = ctx
+        .send(|b| {
+            b.content(format!(
+                "**Dry run, nothing has been written yet**\nBackup created at: {}\n\n{}",
+                created_at, diff
+            ))
+            .components(|c| {
+                c.create_action_row(|row| {
+                    row.create_button(|button| {
+                        button
+                            .custom_id(CustomId::StateRestoreConfirm.to_string())
+                            .label("Restore")
+                            .style(ButtonStyle::Danger)
+                    });
+                    row.create_button(|button| {
+                        button
+                            .custom_id(CustomId::StateRestoreCancel.to_string())
+                            .label("Cancel")
+                            .style(ButtonStyle::Secondary)
+                    })
+                })
+            })
+        })
+        .await?;
+
+    let message = reply.message().await?;
+    ActiveCollectors::start(
+        data,
+        ctx.author().id,
+        message.channel_id,
+        message.id,
+        SessionKind::StateRestore,
+    )
+    .await?;
+    data.pending_restore.set(message.id, backup);
+
+    Ok(())
+}
+
+const HISTORY_PAGE_SIZE: usize = 10;
+
+async fn autocomplete_state_key<'a>(
+    _ctx: Context<'_>,
+    partial: &'a str,
+) -> impl Iterator<Item = String> + 'a {
+    migrations::registry()
+        .into_iter()
+        .map(|schema| schema.key.to_string())
+        .filter(move |key| key.starts_with(partial))
+}
+
+/// Discord caps choice parameters at 25 options, there are more log events than that
+async fn autocomplete_log_kind<'a>(
+    _ctx: Context<'_>,
+    partial: &'a str,
+) -> impl Iterator<Item = String> + 'a {
+    let partial = partial.to_lowercase();
+
+    LogKind::ALL
+        .into_iter()
+        .map(|kind| kind.name().to_string())
+        .filter(move |name| name.to_lowercase().contains(&partial))
+        .take(25)
+}
+
+fn parse_log_kind(event: &str) -> Result<LogKind, Error> {
+    LogKind::from_str(event)
+        .map_err(|_| BotError::User(format!("`{}` is not a logged event...", event)))
+}
+
+/// Page through the audit trail of state changes, newest first
+#[poise::command(slash_command, ephemeral, required_permissions = "ADMINISTRATOR")]
+pub async fn state_history(
+    ctx: Context<'_>,
+    #[description = "Only show changes to this state key"]
+    #[autocomplete = "autocomplete_state_key"]
+    key: Option<String>,
+    #[description = "Only show changes made by this user"] actor: Option<serenity::User>,
+    #[description = "Page number, starting at 1"]
+    #[min = 1]
+    page: Option<usize>,
+) -> Result<(), Error> {
+    let history = StateHistory::load(ctx.data())?;
+    let entries = history.filter(key.as_deref(), actor.map(|x| x.id.0));
+
+    if entries.is_empty() {
+        ctx.say("No state changes found").await?;
+        return Ok(());
+    }
+
+    // Pages end after `HISTORY_PAGE_SIZE` entries or before running past the description limit
+    let mut pages: Vec<String> = vec![String::new()];
+    for entry in entries.iter() {
+        let line = format!("{}\n", entry);
+        let current = pages.last_mut().expect("There is always a page");
+
+        let full = current.lines().count() >= HISTORY_PAGE_SIZE
+            || current.chars().count() + line.chars().count() > DESCRIPTION_LIMIT;
+        if full && !current.is_empty() {
+            pages.push(line);
+        } else {
+            current.push_str(&line);
+        }
+    }
+
+    let page = page.unwrap_or(1).clamp(1, pages.len());
+    let out = pages[page - 1].clone();
+
+    ctx.send(|b| {
+        b.embed(|e| {
+            e.title("State History")
+                .color(BLACK)
+                .description(out)
+                .footer(|f| {
+                    f.text(format!(
+                        "Page {} of {}, {} change(s)",
+                        page,
+                        pages.len(),
+                        entries.len()
+                    ))
+                })
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Post an event to an extra log channel
+#[poise::command(slash_command, ephemeral, required_permissions = "ADMINISTRATOR")]
+pub async fn add_log_route(
+    ctx: Context<'_>,
+    #[description = "Logged event"]
+    #[autocomplete = "autocomplete_log_kind"]
+    event: String,
+    #[description = "Log channel"] channel: serenity::Channel,
+) -> Result<(), Error> {
+    let event = parse_log_kind(&event)?;
+    let data = ctx.data();
+
+    let successful = LogRoutes::add(data, &ctx.into(), event, channel.id()).await?;
+
+    if successful {
+        ctx.say(format!(
+            "{} will now be posted to {}",
+            event,
+            channel.id().get_interactive()
+        ))
+        .await?;
+    } else {
+        ctx.say("Event is already posted to that channel...")
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Stop posting an event to a log channel, leave the channel empty to turn the event off
+#[poise::command(slash_command, ephemeral, required_permissions = "ADMINISTRATOR")]
+pub async fn remove_log_route(
+    ctx: Context<'_>,
+    #[description = "Logged event"]
+    #[autocomplete = "autocomplete_log_kind"]
+    event: String,
+    #[description = "Log channel"] channel: Option<serenity::Channel>,
+) -> Result<(), Error> {
+    let event = parse_log_kind(&event)?;
+    let data = ctx.data();
+    let channel_id = channel.map(|x| x.id());
+
+    let successful = LogRoutes::remove(data, &ctx.into(), event, channel_id).await?;
+
+    match (successful, channel_id) {
+        (true, Some(channel_id)) => {
+            ctx.say(format!(
+                "{} will no longer be posted to {}",
+                event,
+                channel_id.get_interactive()
+            ))
+            .await?
+        }
+        (true, None) => ctx.say(format!("{} is now turned off", event)).await?,
+        (false, _) => ctx.say("Nothing to remove...").await?,
+    };
+
+    Ok(())
+}
+
+/// Send an event back to its default log channel
+#[poise::command(slash_command, ephemeral, required_permissions = "ADMINISTRATOR")]
+pub async fn reset_log_route(
+    ctx: Context<'_>,
+    #[description = "Logged event"]
+    #[autocomplete = "autocomplete_log_kind"]
+    event: String,
+) -> Result<(), Error> {
+    let event = parse_log_kind(&event)?;
+    let data = ctx.data();
+
+    let successful = LogRoutes::reset(data, &ctx.into(), event).await?;
+
+    if successful {
+        ctx.say(format!("{} is posted to its default channel again", event))
+            .await?;
+    } else {
+        ctx.say(format!("{} already uses its default channel...", event))
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Display where each event is logged
+#[poise::command(slash_command, ephemeral, required_permissions = "ADMINISTRATOR")]
+pub async fn list_log_routes(ctx: Context<'_>) -> Result<(), Error> {
+    let data = ctx.data();
+
+    let groups = LogRoutes::load(data)?.describe(data)?;
+
+    ctx.send(|b| {
+        b.embed(|e| {
+            e.title("Log Routes").color(BLACK);
+            for (group, lines) in groups {
+                e.field(group, lines, false);
+            }
+            e
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+const VOICE_STATS_TOP: usize = 10;
+
+/// Show who spent the most time in voice chat, optionally for one user, channel or date range
+#[poise::command(slash_command, ephemeral)]
+pub async fn voice_stats(
+    ctx: Context<'_>,
+    #[description = "Only count this user"] user: Option<serenity::User>,
+    #[description = "Only count this voice channel"]
+    #[channel_types("Voice", "Stage")]
+    channel: Option<serenity::Channel>,
+    #[description = "First day counted, YYYY-MM-DD (UTC), up to a year ago"] from: Option<String>,
+    #[description = "Last day counted, as YYYY-MM-DD (UTC)"] to: Option<String>,
+) -> Result<(), Error> {
+    let filter = StatsFilter {
+        user_id: user.as_ref().map(|x| x.id.0),
+        channel_id: channel.as_ref().map(|x| x.id().0),
+        from_day: from.as_deref().map(parse_day).transpose()?,
+        to_day: to.as_deref().map(parse_day).transpose()?,
+    };
+
+    if filter
+        .from_day
+        .is_some_and(|x| x < oldest_day(Utc::now().timestamp()))
+    {
+        return Err(BotError::User(
+            "Voice stats only go back a year, pick a later first day...".to_string(),
+        ));
+    }
+
+    let stats = VoiceStats::load(ctx.data())?;
+    let board = stats.leaderboard(&filter);
+
+    if board.total == 0 {
+        ctx.say("No voice chat time found").await?;
+        return Ok(());
+    }
+
+    let list = |totals: &[(u64, i64)], mention: fn(u64) -> String| {
+        totals
+            .iter()
+            .take(VOICE_STATS_TOP)
+            .enumerate()
+            .map(|(i, (id, seconds))| {
+                format!("{}. {} {}", i + 1, mention(*id), format_duration(*seconds))
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+
+    let mut scope = vec![];
+    if let Some(user) = &user {
+        scope.push(user.id.get_interactive());
+    }
+    if let Some(channel) = &channel {
+        scope.push(channel.id().get_interactive());
+    }
+    scope.push(format!(
+        "{} to {}",
+        from.as_deref().unwrap_or("a year ago"),
+        to.as_deref().unwrap_or("today")
+    ));
+
+    ctx.send(|b| {
+        b.embed(|e| {
+            e.title("Voice Stats")
+                .color(BLACK)
+                .description(scope.join(", "))
+                .field("Total", format_duration(board.total), false);
+
+            // A leaderboard of a single entry says nothing the total doesn't
+            if filter.user_id.is_none() {
+                e.field(
+                    "Top Members",
+                    list(&board.users, |id| UserId(id).get_interactive()),
+                    true,
+                );
+            }
+            if filter.channel_id.is_none() {
+                e.field(
+                    "Top Channels",
+                    list(&board.channels, |id| ChannelId(id).get_interactive()),
+                    true,
+                );
+            }
+
+            e.footer(|f| f.text("Ongoing sessions are counted once they end"))
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+fn parse_day(value: &str) -> Result<i64, BotError> {
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| BotError::User(format!("`{}` is not a YYYY-MM-DD date", value)))?;
+
+    let timestamp = date.and_time(NaiveTime::MIN).and_utc().timestamp();
+    Ok(day_of(timestamp))
+}
+
+const INVITES_TOP: usize = 15;
+
+/// Show how many members each inviter brought in over the last week, month and all time
+#[poise::command(slash_command, ephemeral, required_permissions = "ADMINISTRATOR")]
+pub async fn invites(ctx: Context<'_>) -> Result<(), Error> {
+    let joins = InviteJoins::load(ctx.data())?;
+
+    if joins.0.is_empty() {
+        ctx.say("No joins recorded yet").await?;
+        return Ok(());
+    }
+
+    let now = Utc::now().timestamp();
+    let all_time = joins.per_inviter(0);
+    let month = joins.per_inviter(now - 30 * 86400);
+    let week = joins.per_inviter(now - 7 * 86400);
+
+    let mut inviters: Vec<(&Option<u64>, &usize)> = all_time.iter().collect();
+    inviters.sort_by_key(|x| std::cmp::Reverse(*x.1));
+
+    let mut out = String::new();
+    for (i, (inviter, total)) in inviters.iter().take(INVITES_TOP).enumerate() {
+        let name = match inviter {
+            Some(inviter) => UserId(*inviter).get_interactive(),
+            None => "*Unknown*".to_string(),
+        };
+
+        out.push_str(&format!(
+            "{}. {}: **{}** total, {} this month, {} this week\n",
+            i + 1,
+            name,
+            total,
+            month.get(*inviter).unwrap_or(&0),
+            week.get(*inviter).unwrap_or(&0)
+        ));
+    }
+
+    ctx.send(|b| {
+        b.embed(|e| {
+            e.title("Invites")
+                .color(BLACK)
+                .description(out)
+                .footer(|f| f.text(format!("{} join(s) recorded", joins.0.len())))
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Give accounts younger than the minimum age a quarantine role instead of the follower role
+#[poise::command(slash_command, ephemeral, required_permissions = "ADMINISTRATOR")]
+pub async fn set_quarantine(
+    ctx: Context<'_>,
+    #[description = "Minimum account age in days"]
+    #[min = 1]
+    min_age_days: i64,
+    #[description = "Role given to quarantined members"] role: serenity::Role,
+) -> Result<(), Error> {
+    let settings = QuarantineSettings {
+        min_account_age_days: min_age_days,
+        role_id: role.id.0,
+    };
+
+    let changed = Quarantine::configure(ctx.data(), &ctx.into(), Some(settings)).await?;
+
+    if changed {
+        ctx.say(format!(
+            "Accounts younger than {} day(s) will now get {}",
+            min_age_days,
+            role.id.get_interactive()
+        ))
+        .await?;
+    } else {
+        ctx.say("Quarantine already uses these settings...").await?;
+    }
+
+    Ok(())
+}
+
+/// Stop quarantining new accounts, pending alerts keep working
+#[poise::command(slash_command, ephemeral, required_permissions = "ADMINISTRATOR")]
+pub async fn disable_quarantine(ctx: Context<'_>) -> Result<(), Error> {
+    let changed = Quarantine::configure(ctx.data(), &ctx.into(), None).await?;
+
+    if changed {
+        ctx.say("New accounts will no longer be quarantined")
+            .await?;
+    } else {
+        ctx.say("Quarantine is already disabled...").await?;
+    }
+
+    Ok(())
+}
+
+/// Page through everything recorded about a member, newest first
+#[poise::command(slash_command, ephemeral, check = "is_on_admin_list")]
+pub async fn history(
+    ctx: Context<'_>,
+    #[description = "Selected user"] user: serenity::User,
+) -> Result<(), Error> {
+    MemberHistoryMenu::open(ctx, &user).await
+}
+
+/// Choose whether an event is written to a structured event sink
+#[poise::command(slash_command, ephemeral, required_permissions = "ADMINISTRATOR")]
+pub async fn set_event_sink(
+    ctx: Context<'_>,
+    #[description = "Event sink"] sink: SinkKind,
+    #[description = "Logged event"]
+    #[autocomplete = "autocomplete_log_kind"]
+    event: String,
+    #[description = "Write this event to the sink"] enabled: bool,
+) -> Result<(), Error> {
+    let event = parse_log_kind(&event)?;
+    let data = ctx.data();
+
+    let changed = SinkFilters::set(data, &ctx.into(), sink, event, enabled).await?;
+
+    match (changed, enabled) {
+        (true, true) => {
+            ctx.say(format!("{} is now written to the {} sink", event, sink))
+                .await?
+        }
+        (true, false) => {
+            ctx.say(format!(
+                "{} is no longer written to the {} sink",
+                event, sink
+            ))
+            .await?
+        }
+        (false, _) => ctx.say("Nothing to change...").await?,
+    };
+
+    Ok(())
+}
+
+/// Display the configured event sinks and the events they skip
+#[poise::command(slash_command, ephemeral, required_permissions = "ADMINISTRATOR")]
+pub async fn list_event_sinks(ctx: Context<'_>) -> Result<(), Error> {
+    let data = ctx.data();
+    let filters = SinkFilters::load(data)?;
+
+    let configured = data.event_sinks.configured();
+    if configured.is_empty() {
+        ctx.say("No event sinks are configured, set EVENT_LOG_DIR or EVENT_WEBHOOK_URL")
+            .await?;
+        return Ok(());
+    }
+
+    let lines: Vec<String> = configured
+        .iter()
+        .map(|sink| {
+            let disabled = match filters.0.get(sink) {
+                Some(kinds) => kinds
+                    .iter()
+                    .map(|x| x.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                None => "*None*".to_string(),
+            };
+            format!("**{}**\nSkipped events: {}", sink, disabled)
+        })
+        .collect();
+
+    ctx.say(lines.join("\n\n")).await?;
+
+    Ok(())
+}
+
 /// Add a game / channel union to the list of games that support guild applications
 #[poise::command(slash_command, ephemeral, required_permissions = "ADMINISTRATOR")]
 pub async fn add_guild_application(
@@ -271,8 +970,7 @@ pub async fn add_guild_application(
     let channel_id: u64 = channel.id().into();
     let data = ctx.data();
 
-    let mut guild_apply = GuildApply::load(data)?;
-    let successful = guild_apply.add(data, game_name.clone(), channel_id)?;
+    let successful = GuildApply::add(data, &ctx.into(), game_name.clone(), channel_id).await?;
 
     if successful {
         ctx.say(format!("{} was added to the apply list!", game_name))
@@ -308,8 +1006,7 @@ pub async fn remove_guild_application(
 ) -> Result<(), Error> {
     let data = ctx.data();
 
-    let mut guild_apply = GuildApply::load(data)?;
-    let successful = guild_apply.remove(data, game_name.clone())?;
+    let successful = GuildApply::remove(data, &ctx.into(), game_name.clone()).await?;
 
     if successful {
         ctx.say(format!("{} was removed from the apply list!", game_name))
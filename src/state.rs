@@ -1,21 +1,57 @@
 use self::{
-    active_collectors::ActiveCollectors, games::Games, guild_apply::GuildApply,
-    role_backups::RoleBackups, t_rooms::TRooms,
+    active_collectors::ActiveCollectors,
+    backup::PendingRestore,
+    game_activity::GameActivity,
+    games::Games,
+    guild_apply::GuildApply,
+    history::{Origin, StateHistory},
+    invite_joins::InviteJoins,
+    log_routes::LogRoutes,
+    member_history::MemberHistory,
+    member_snapshots::MemberSnapshots,
+    quarantine::Quarantine,
+    role_backups::RoleBackups,
+    sink_filters::SinkFilters,
+    t_rooms::TRooms,
+    voice_stats::VoiceStats,
+};
+use crate::log_channel::{
+    dispatcher::LogDispatcher, invite_tracking::InviteCache, message_events::MessageCache,
+    sinks::EventSinks, thread_events::ThreadCache,
 };
 use crate::state::admins::Admins;
+use cache::StateCache;
+pub use migrations::VersionedStateExt;
 use poise::serenity_prelude::{Cache, Role, RoleId};
 use serde::{Deserialize, Serialize};
-use shuttle_persist::{PersistError, PersistInstance};
+pub use snowflake_set::SnowflakeSet;
+use std::collections::HashMap;
+pub use store::{StateBackend, StateStore, StoreError};
 
 pub mod active_collectors;
 pub mod admins;
+pub mod backup;
+pub mod cache;
+pub mod game_activity;
 pub mod games;
 pub mod guild_apply;
+pub mod history;
+pub mod invite_joins;
+pub mod log_routes;
+pub mod member_history;
+pub mod member_snapshots;
+pub mod migrations;
+pub mod quarantine;
 pub mod role_backups;
+pub mod sink_filters;
+pub mod snowflake_set;
+pub mod store;
 pub mod t_rooms;
+pub mod voice_stats;
 
 pub struct Data {
-    pub bot_state: PersistInstance,
+    pub bot_state: StateBackend,
+    pub state_cache: StateCache,
     pub minor_events_channel: String,
     pub major_events_channel: String,
     pub follower_role: String,
@@ -24,15 +60,32 @@ pub struct Data {
     pub guild_apply_roles: Vec<String>,
     pub needs_to_apply_role: String,
     pub needs_to_apply_channel: String,
+    pub message_cache: MessageCache,
+    pub log_dispatcher: LogDispatcher,
+    pub invite_cache: InviteCache,
+    pub event_sinks: EventSinks,
+    pub thread_cache: ThreadCache,
+    pub pending_restore: PendingRestore,
 }
 
-pub fn init_all_state(data: &Data) -> Result<(), anyhow::Error> {
-    Admins::init_state(data)?;
-    Games::init_state(data)?;
-    ActiveCollectors::init_state(data)?;
-    RoleBackups::init_state(data)?;
-    TRooms::init_state(data)?;
-    GuildApply::init_state(data)?;
+pub async fn init_all_state(data: &Data) -> Result<(), anyhow::Error> {
+    migrations::run_all(&data.bot_state)?;
+
+    Admins::init_state(data).await?;
+    Games::init_state(data).await?;
+    ActiveCollectors::init_state(data).await?;
+    RoleBackups::init_state(data).await?;
+    TRooms::init_state(data).await?;
+    GuildApply::init_state(data).await?;
+    StateHistory::init_state(data).await?;
+    LogRoutes::init_state(data).await?;
+    VoiceStats::init_state(data).await?;
+    InviteJoins::init_state(data).await?;
+    Quarantine::init_state(data).await?;
+    SinkFilters::init_state(data).await?;
+    MemberHistory::init_state(data).await?;
+    MemberSnapshots::init_state(data).await?;
+    GameActivity::init_state(data).await?;
 
     Ok(())
 }
@@ -41,7 +94,7 @@ pub trait BotStateInitialization: std::default::Default {
     fn init_state_inner<'a, T: Default + Serialize>(
         &'a self,
         data: &'a Data,
-    ) -> Result<(), PersistError>
+    ) -> Result<(), StoreError>
     where
         for<'de> Self: Deserialize<'de>,
         Self: Serialize,
@@ -49,16 +102,10 @@ pub trait BotStateInitialization: std::default::Default {
         let key = &self.get_key();
         let state = &data.bot_state;
 
-        let state_struct = state.load::<Self>(key);
+        let state_struct = state.load_versioned::<Self>(key);
 
-        match state_struct {
-            Ok(_) => (),
-            Err(error) => match error {
-                PersistError::Open(_) => {
-                    let _result = state.save::<Self>(key, Self::default())?;
-                }
-                _ => (),
-            },
+        if let Err(StoreError::NotFound(_)) = state_struct {
+            state.save_versioned::<Self>(key, Self::default())?;
         }
 
         Ok(())
@@ -66,125 +113,148 @@ pub trait BotStateInitialization: std::default::Default {
 
     fn get_key(&self) -> String;
 
-    fn init_state(data: &Data) -> Result<(), anyhow::Error>
+    /// Loads, modifies and saves this state while holding its key's lock,
+    /// so concurrent interactions can never overwrite each other's changes
+    async fn update<R, F>(data: &Data, f: F) -> Result<R, anyhow::Error>
+    where
+        F: FnOnce(&mut Self) -> R,
+        for<'de> Self: Deserialize<'de>,
+        Self: Serialize + Clone + Send + Sync + 'static,
+    {
+        let key = Self::default().get_key();
+        let result = data.state_cache.update(&data.bot_state, &key, f).await?;
+
+        Ok(result)
+    }
+
+    async fn init_state(data: &Data) -> Result<(), anyhow::Error>
     where
         for<'de> Self: Deserialize<'de>,
         Self: Serialize,
     {
         let data_struct = Self::default();
-        let result = data_struct.init_state_inner::<Self>(&data);
+        let result = data_struct.init_state_inner::<Self>(data);
         match result {
             Ok(_) => Ok(()),
-            Err(e) => Err(anyhow::anyhow!("{}", e)),
+            Err(e) => Err(e.into()),
         }
     }
 }
 
 pub trait SnowflakeStorage: BotStateInitialization + Clone {
+    type Id: Copy + Into<u64> + From<u64> + Send + 'static;
+
     fn load(data: &Data) -> Result<Self, anyhow::Error>
     where
         for<'de> Self: Deserialize<'de>;
 
-    fn add(&mut self, data: &Data, id: u64) -> Result<bool, anyhow::Error>
+    async fn add(data: &Data, origin: &Origin, id: Self::Id) -> Result<bool, anyhow::Error>
     where
-        for<'de> Self: Serialize,
+        for<'de> Self: Deserialize<'de>,
+        Self: Serialize + Send + Sync + 'static,
     {
-        if !self.snowflake_found(&id) {
-            self.push_snowflake(id);
-
-            let result = data.bot_state.save::<Self>(&self.get_key(), self.clone());
-            match result {
-                Ok(_) => return Ok(true),
-                Err(e) => return Err(anyhow::anyhow!("{}", e)),
-            };
+        let added = Self::update(data, |state| state.snowflakes_mut().insert(id)).await?;
+
+        if added {
+            let key = Self::default().get_key();
+            StateHistory::record(data, origin, &key, None::<u64>, Some(id.into())).await?;
         }
 
-        Ok(false)
+        Ok(added)
     }
 
-    fn remove(&mut self, data: &Data, id: u64) -> Result<bool, anyhow::Error>
+    async fn remove(data: &Data, origin: &Origin, id: Self::Id) -> Result<bool, anyhow::Error>
     where
-        for<'de> Self: Serialize,
+        for<'de> Self: Deserialize<'de>,
+        Self: Serialize + Send + Sync + 'static,
     {
-        let index = self.snowflakes().position(|&i| i == id);
-
-        match index {
-            Some(index) => {
-                self.remove_snowflake(index);
-                let result = data.bot_state.save::<Self>(&self.get_key(), self.clone());
-                match result {
-                    Ok(_) => return Ok(true),
-                    Err(e) => return Err(anyhow::anyhow!("{}", e)),
-                }
-            }
-            None => return Ok(false),
-        };
+        let removed = Self::update(data, |state| state.snowflakes_mut().remove(&id)).await?;
+
+        if removed {
+            let key = Self::default().get_key();
+            StateHistory::record(data, origin, &key, Some(id.into()), None::<u64>).await?;
+        }
+
+        Ok(removed)
     }
 
-    fn snowflake_found(&self, id: &u64) -> bool;
-    fn push_snowflake(&mut self, id: u64);
-    fn remove_snowflake(&mut self, index: usize);
-    fn snowflakes(&self) -> std::slice::Iter<'_, u64>;
+    fn snowflakes(&self) -> &SnowflakeSet<Self::Id>;
+    fn snowflakes_mut(&mut self) -> &mut SnowflakeSet<Self::Id>;
 }
 
+// Only the disabled guild application commands use this
+#[allow(dead_code)]
 pub trait SnowflakeHashmapStorage: BotStateInitialization + Clone {
     fn load(data: &Data) -> Result<Self, anyhow::Error>
     where
         for<'de> Self: Deserialize<'de>;
 
-    fn add(&mut self, data: &Data, key: String, value: u64) -> Result<bool, anyhow::Error>
+    async fn add(
+        data: &Data,
+        origin: &Origin,
+        key: String,
+        value: u64,
+    ) -> Result<bool, anyhow::Error>
     where
-        for<'de> Self: Serialize,
+        for<'de> Self: Deserialize<'de>,
+        Self: Serialize + Send + Sync + 'static,
     {
-        if !self.snowflake_key_found(&key) && !self.snowflake_value_found(&value) {
-            self.push_kv_inner(key, value);
+        let entry = HashMap::from([(key.clone(), value)]);
 
-            let result = data.bot_state.save::<Self>(&self.get_key(), self.clone());
-            match result {
-                Ok(_) => return Ok(true),
-                Err(e) => return Err(anyhow::anyhow!("{}", e)),
+        let added = Self::update(data, |state| {
+            if state.snowflake_key_found(&key) || state.snowflake_value_found(&value) {
+                return false;
             }
+
+            state.push_kv_inner(key, value);
+            true
+        })
+        .await?;
+
+        if added {
+            let state_key = Self::default().get_key();
+            StateHistory::record(data, origin, &state_key, None::<u64>, entry).await?;
         }
 
-        Ok(false)
+        Ok(added)
     }
 
-    fn remove(&mut self, data: &Data, key: String) -> Result<bool, anyhow::Error>
+    async fn remove(data: &Data, origin: &Origin, key: String) -> Result<bool, anyhow::Error>
     where
-        for<'de> Self: Serialize,
+        for<'de> Self: Deserialize<'de>,
+        Self: Serialize + Send + Sync + 'static,
     {
-        let index = self.all().position(|i| i.0 == &key);
-
-        match index {
-            Some(_index) => {
-                self.remove_inner(key);
-                let result = data.bot_state.save::<Self>(&self.get_key(), self.clone());
-                match result {
-                    Ok(_) => return Ok(true),
-                    Err(e) => return Err(anyhow::anyhow!("{}", e)),
-                }
-            }
-            None => Ok(false),
+        let removed = Self::update(data, |state| {
+            let value = state.all().find(|(k, _)| **k == key).map(|(_, v)| *v)?;
+
+            state.remove_inner(key.clone());
+            Some(HashMap::from([(key, value)]))
+        })
+        .await?;
+
+        if let Some(entry) = &removed {
+            let state_key = Self::default().get_key();
+            StateHistory::record(data, origin, &state_key, entry, None::<u64>).await?;
         }
+
+        Ok(removed.is_some())
     }
 
-    fn snowflake_key_found(&self, key: &String) -> bool;
+    fn snowflake_key_found(&self, key: &str) -> bool;
     fn snowflake_value_found(&self, value: &u64) -> bool;
     fn push_kv_inner(&mut self, key: String, value: u64);
     fn remove_inner(&mut self, key: String);
     fn all(&self) -> std::collections::hash_map::Iter<'_, std::string::String, u64>;
 }
-pub trait SnowflakesToRoles: SnowflakeStorage {
+pub trait SnowflakesToRoles: SnowflakeStorage<Id = RoleId> {
     fn to_roles(&self, cache: &Cache) -> Vec<Role> {
         let snowflakes = self.snowflakes();
         let mut roles: Vec<Role> = vec![];
 
-        for snowflake in snowflakes {
-            let role = RoleId(*snowflake);
+        for role in snowflakes.iter() {
             let role = role.to_role_cached(cache);
-            match role {
-                Some(value) => roles.push(value),
-                None => (),
+            if let Some(value) = role {
+                roles.push(value)
             }
         }
 
@@ -1,56 +1,190 @@
-use super::SnowflakeStorage;
 use crate::state::BotStateInitialization;
 use crate::Data;
+use chrono::{Duration, Utc};
 use serde::{Deserialize, Serialize};
 
 const KEY: &str = "active_collectors";
 
+/// Every live component session, identified by the message carrying its components
 #[derive(Serialize, Deserialize, Clone, Default, Debug)]
-pub struct ActiveCollectors(pub Vec<u64>);
+pub struct ActiveCollectors(pub Vec<Session>);
 
-impl BotStateInitialization for ActiveCollectors {
-    fn get_key(&self) -> String {
-        KEY.to_string()
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Session {
+    pub message_id: u64,
+    pub channel_id: u64,
+    pub owner: u64,
+    /// Unix timestamp in seconds
+    pub expires_at: i64,
+    pub kind: SessionKind,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum SessionKind {
+    /// Ephemeral dropdown opened from the pick games menu
+    PickGames,
+    /// Restore waiting for its owner to confirm, the backup itself is in `Data::pending_restore`
+    StateRestore,
+    /// Member history pager, holds the member and the page currently shown
+    MemberHistory { user_id: u64, page: usize },
+    /// Game role suggestion sent to a member's DMs
+    GameSuggestion { guild_id: u64, role_id: u64 },
+}
+
+pub enum SessionLookup {
+    Active,
+    NotOwner,
+    Expired,
+    /// No session was ever registered for the message, or it was pruned long ago
+    NotFound,
+}
+
+impl SessionKind {
+    fn lifetime(&self) -> Duration {
+        match self {
+            Self::PickGames => Duration::minutes(15),
+            Self::StateRestore => Duration::minutes(10),
+            Self::MemberHistory { .. } => Duration::minutes(15),
+            Self::GameSuggestion { .. } => Duration::days(7),
+        }
+    }
+}
+
+impl Session {
+    pub fn is_expired(&self) -> bool {
+        Utc::now().timestamp() >= self.expires_at
     }
+}
 
-    fn init_state_inner<T: Default + Serialize>(
-        &self,
+// Main functionality
+impl ActiveCollectors {
+    /// Registers a session for the message that was just sent, dropping expired ones on the way
+    pub async fn start<O: Into<u64>, C: Into<u64>, M: Into<u64>>(
         data: &Data,
-    ) -> Result<(), shuttle_persist::PersistError>
-    where
-        for<'de> Self: Deserialize<'de>,
-        Self: Serialize,
-    {
-        let key = &self.get_key();
-        let state = &data.bot_state;
-        let _state_struct = state.save::<Self>(key, Self::default()).unwrap();
+        owner: O,
+        channel_id: C,
+        message_id: M,
+        kind: SessionKind,
+    ) -> Result<(), anyhow::Error> {
+        let message_id: u64 = message_id.into();
+        let session = Session {
+            message_id,
+            channel_id: channel_id.into(),
+            owner: owner.into(),
+            expires_at: (Utc::now() + kind.lifetime()).timestamp(),
+            kind,
+        };
 
-        Ok(())
+        Self::update(data, |state| {
+            state
+                .0
+                .retain(|x| !x.is_expired() && x.message_id != message_id);
+            state.0.push(session);
+        })
+        .await
+    }
+
+    /// Finds the session behind a component interaction, an expired session is removed
+    pub async fn find<M: Into<u64>, U: Into<u64>>(
+        data: &Data,
+        message_id: M,
+        user_id: U,
+    ) -> Result<SessionLookup, anyhow::Error> {
+        let message_id: u64 = message_id.into();
+        let user_id: u64 = user_id.into();
+
+        let session = Self::load(data)?
+            .0
+            .into_iter()
+            .find(|x| x.message_id == message_id);
+
+        let lookup = match session {
+            None => SessionLookup::NotFound,
+            Some(session) if session.is_expired() => {
+                Self::end(data, message_id).await?;
+                SessionLookup::Expired
+            }
+            Some(session) if session.owner != user_id => SessionLookup::NotOwner,
+            Some(_) => SessionLookup::Active,
+        };
+
+        Ok(lookup)
+    }
+
+    /// The session behind a message, without checking its owner or expiry
+    pub fn get<M: Into<u64>>(data: &Data, message_id: M) -> Result<Option<Session>, anyhow::Error> {
+        let message_id: u64 = message_id.into();
+
+        Ok(Self::load(data)?
+            .0
+            .into_iter()
+            .find(|x| x.message_id == message_id))
+    }
+
+    /// Removes a session and hands it back, `None` if it was already gone
+    pub async fn end<M: Into<u64>>(
+        data: &Data,
+        message_id: M,
+    ) -> Result<Option<Session>, anyhow::Error> {
+        let message_id: u64 = message_id.into();
+
+        Self::update(data, |state| {
+            let index = state.0.iter().position(|x| x.message_id == message_id)?;
+            Some(state.0.remove(index))
+        })
+        .await
+    }
+
+    /// Drops every expired session, returns how many were removed
+    pub async fn prune(data: &Data) -> Result<usize, anyhow::Error> {
+        Self::update(data, |state| {
+            let before = state.0.len();
+            state.0.retain(|x| !x.is_expired());
+            before - state.0.len()
+        })
+        .await
     }
 }
 
-impl SnowflakeStorage for ActiveCollectors {
-    fn load(data: &Data) -> Result<Self, anyhow::Error>
+// Core functionality
+impl ActiveCollectors {
+    pub fn load(data: &Data) -> Result<Self, anyhow::Error>
     where
         for<'de> Self: Deserialize<'de>,
     {
-        let data = data.bot_state.load::<Self>(KEY).unwrap();
-        Ok(data)
+        let data = data.state_cache.load::<Self>(&data.bot_state, KEY);
+        match data {
+            Ok(data) => Ok(data),
+            Err(e) => Err(e.into()),
+        }
     }
+}
 
-    fn snowflake_found(&self, id: &u64) -> bool {
-        self.0.contains(id)
+impl BotStateInitialization for ActiveCollectors {
+    fn get_key(&self) -> String {
+        KEY.to_string()
     }
 
-    fn push_snowflake(&mut self, id: u64) {
-        self.0.push(id);
-    }
+    async fn init_state(data: &Data) -> Result<(), anyhow::Error>
+    where
+        for<'de> Self: Deserialize<'de>,
+        Self: Serialize,
+    {
+        let data_struct = Self::default();
+        let result = data_struct.init_state_inner::<Self>(data);
+        match result {
+            Ok(_) => (),
+            Err(e) => return Err(e.into()),
+        };
 
-    fn snowflakes(&self) -> std::slice::Iter<'_, u64> {
-        self.0.iter()
-    }
+        let expired = Self::prune(data).await?;
+        let resumed = Self::load(data)?.0.len();
+        tracing::info!(
+            "Component sessions: {} resumed, {} expired while offline",
+            resumed,
+            expired
+        );
 
-    fn remove_snowflake(&mut self, index: usize) {
-        self.0.remove(index);
+        Ok(())
     }
 }
@@ -1,4 +1,4 @@
-use super::SnowflakeStorage;
+use super::{SnowflakeSet, SnowflakeStorage};
 use crate::extensions::InteractiveSnowflakeExt;
 use crate::Data;
 use crate::{constants::MASTER_ADMIN, state::BotStateInitialization};
@@ -8,21 +8,18 @@ use serde::{Deserialize, Serialize};
 const KEY: &str = "admins";
 
 #[derive(Serialize, Deserialize, Clone)]
-pub struct Admins(pub Vec<u64>);
+pub struct Admins(pub SnowflakeSet<UserId>);
 
 impl Default for Admins {
     fn default() -> Self {
-        Self(vec![MASTER_ADMIN])
+        Self([UserId(MASTER_ADMIN)].into_iter().collect())
     }
 }
 
 impl std::fmt::Display for Admins {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let raw_admins = &self.0;
-
         let mut admins = String::new();
-        for admin in raw_admins {
-            let admin = UserId(*admin);
+        for admin in self.0.iter() {
             let admin = admin.get_interactive();
             admins.push_str(&format!("{}\n", admin));
         }
@@ -37,30 +34,24 @@ impl BotStateInitialization for Admins {
 }
 
 impl SnowflakeStorage for Admins {
+    type Id = UserId;
+
     fn load(data: &Data) -> Result<Self, anyhow::Error>
     where
         for<'de> Self: Deserialize<'de>,
     {
-        let data = data.bot_state.load::<Self>(KEY);
+        let data = data.state_cache.load::<Self>(&data.bot_state, KEY);
         match data {
             Ok(data) => Ok(data),
-            Err(e) => Err(anyhow::anyhow!("{}", e)),
+            Err(e) => Err(e.into()),
         }
     }
 
-    fn snowflake_found(&self, id: &u64) -> bool {
-        self.0.contains(id)
-    }
-
-    fn push_snowflake(&mut self, id: u64) {
-        self.0.push(id);
-    }
-
-    fn snowflakes(&self) -> std::slice::Iter<'_, u64> {
-        self.0.iter()
+    fn snowflakes(&self) -> &SnowflakeSet<Self::Id> {
+        &self.0
     }
 
-    fn remove_snowflake(&mut self, index: usize) {
-        self.0.remove(index);
+    fn snowflakes_mut(&mut self) -> &mut SnowflakeSet<Self::Id> {
+        &mut self.0
     }
 }
@@ -0,0 +1,202 @@
+use super::{
+    migrations::{self, StateEnvelope},
+    StateStore, StoreError,
+};
+use crate::Data;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::BTreeMap, sync::Mutex};
+
+const BACKUP_FORMAT: &str = "mythicc-state-backup";
+const BACKUP_VERSION: u32 = 1;
+
+/// Live component sessions belong to the running bot, so they are never backed up or overwritten
+const SKIPPED_KEYS: &[&str] = &["active_collectors"];
+
+/// Every persisted state key, dumped into a single JSON document
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StateBackup {
+    format: String,
+    version: u32,
+    pub created_at: String,
+    pub keys: BTreeMap<String, BackupEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BackupEntry {
+    pub version: u32,
+    pub data: Value,
+}
+
+impl StateBackup {
+    pub fn create(data: &Data) -> Result<Self, anyhow::Error> {
+        let mut keys = BTreeMap::new();
+
+        for schema in migrations::registry() {
+            if SKIPPED_KEYS.contains(&schema.key) {
+                continue;
+            }
+
+            match data.bot_state.load::<StateEnvelope>(schema.key) {
+                Ok(envelope) => {
+                    let entry = BackupEntry {
+                        version: envelope.version,
+                        data: envelope.payload()?,
+                    };
+                    keys.insert(schema.key.to_string(), entry);
+                }
+                Err(StoreError::NotFound(_)) => (),
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(Self {
+            format: BACKUP_FORMAT.to_string(),
+            version: BACKUP_VERSION,
+            created_at: Utc::now().to_rfc3339(),
+            keys,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, anyhow::Error> {
+        Ok(serde_json::to_vec_pretty(self)?)
+    }
+
+    /// Parses an uploaded backup, then migrates and validates every key in it.
+    /// Nothing is written to state here
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+        let mut backup: Self = serde_json::from_slice(bytes)
+            .map_err(|e| anyhow::anyhow!("File is not a valid state backup: {}", e))?;
+
+        if backup.format != BACKUP_FORMAT {
+            return Err(anyhow::anyhow!("File is not a state backup"));
+        }
+
+        if backup.version != BACKUP_VERSION {
+            return Err(anyhow::anyhow!(
+                "Unsupported backup version {}, expected {}",
+                backup.version,
+                BACKUP_VERSION
+            ));
+        }
+
+        for (key, entry) in backup.keys.iter_mut() {
+            let schema = migrations::schema(key)
+                .ok_or_else(|| anyhow::anyhow!("Backup contains unknown state key '{}'", key))?;
+
+            if entry.version > schema.version {
+                return Err(anyhow::anyhow!(
+                    "Backup key '{}' is at version {}, newer than this build's version {}",
+                    key,
+                    entry.version,
+                    schema.version
+                ));
+            }
+
+            if entry.version < schema.version {
+                entry.data = migrations::migrate(&schema, entry.version, entry.data.take())?;
+                entry.version = schema.version;
+            }
+
+            (schema.validate)(&entry.data)
+                .map_err(|e| anyhow::anyhow!("Backup key '{}' is invalid: {}", key, e))?;
+        }
+
+        Ok(backup)
+    }
+
+    /// Describes what restoring this backup would change, one line per state key
+    pub fn diff(&self, data: &Data) -> Result<Vec<String>, anyhow::Error> {
+        let current = Self::create(data)?;
+        let mut lines = vec![];
+
+        for schema in migrations::registry() {
+            let key = schema.key;
+            if SKIPPED_KEYS.contains(&key) {
+                continue;
+            }
+
+            let before = current.keys.get(key).map(|x| &x.data);
+            let after = self.keys.get(key).map(|x| &x.data);
+
+            let line = match (before, after) {
+                (_, None) => format!("`{}`: not in backup, left untouched", key),
+                (None, Some(_)) => format!("`{}`: will be created", key),
+                (Some(before), Some(after)) if before == after => {
+                    format!("`{}`: unchanged", key)
+                }
+                (Some(before), Some(after)) => {
+                    format!("`{}`: {}", key, describe_change(before, after))
+                }
+            };
+            lines.push(line);
+        }
+
+        Ok(lines)
+    }
+
+    /// Overwrites every key contained in the backup
+    pub async fn restore(&self, data: &Data) -> Result<(), anyhow::Error> {
+        for (key, entry) in self.keys.iter() {
+            if SKIPPED_KEYS.contains(&key.as_str()) {
+                continue;
+            }
+
+            let envelope = StateEnvelope::new(entry.version, &entry.data);
+
+            data.state_cache
+                .write_with(key, || data.bot_state.save(key, envelope))
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The backup shown by the latest `/restore_state` dry run, only ever kept in memory.
+///
+/// A new dry run replaces it, so a single backup is held at most
+#[derive(Default)]
+pub struct PendingRestore(Mutex<Option<(u64, StateBackup)>>);
+
+impl PendingRestore {
+    pub fn set<M: Into<u64>>(&self, message_id: M, backup: StateBackup) {
+        let mut pending = self.0.lock().expect("PendingRestore mutex poisoned");
+        *pending = Some((message_id.into(), backup));
+    }
+
+    /// Hands out the backup if it belongs to the dry run posted as `message_id`
+    pub fn take<M: Into<u64>>(&self, message_id: M) -> Option<StateBackup> {
+        let message_id = message_id.into();
+        let mut pending = self.0.lock().expect("PendingRestore mutex poisoned");
+
+        match pending.as_ref() {
+            Some((id, _)) if *id == message_id => pending.take().map(|(_, backup)| backup),
+            _ => None,
+        }
+    }
+}
+
+fn describe_change(before: &Value, after: &Value) -> String {
+    match (before, after) {
+        (Value::Array(before), Value::Array(after)) => {
+            let added = after.iter().filter(|x| !before.contains(x)).count();
+            let removed = before.iter().filter(|x| !after.contains(x)).count();
+            format!("{} entries added, {} removed", added, removed)
+        }
+        (Value::Object(before), Value::Object(after)) => {
+            let added = after.keys().filter(|x| !before.contains_key(*x)).count();
+            let removed = before.keys().filter(|x| !after.contains_key(*x)).count();
+            let changed = after
+                .iter()
+                .filter(|(k, v)| before.get(*k).map(|x| x != *v).unwrap_or(false))
+                .count();
+            format!(
+                "{} entries added, {} removed, {} changed",
+                added, removed, changed
+            )
+        }
+        _ => "will be replaced".to_string(),
+    }
+}
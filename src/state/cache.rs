@@ -0,0 +1,196 @@
+use super::{StateStore, StoreError, VersionedStateExt};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    any::Any,
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// Typed in-memory copy of every state key, read through from and written through to the store.
+///
+/// Each key has an async lock that an update holds for its whole load -> modify -> save,
+/// so waiting interactions yield instead of blocking a runtime thread
+#[derive(Default)]
+pub struct StateCache(Mutex<HashMap<String, Arc<CacheSlot>>>);
+
+#[derive(Default)]
+struct CacheSlot {
+    lock: tokio::sync::Mutex<()>,
+    /// Only held for short reads and swaps, never while saving
+    value: Mutex<Option<Box<dyn Any + Send + Sync>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+pub struct CacheStats {
+    pub key: String,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl StateCache {
+    pub fn load<T>(&self, store: &impl StateStore, key: &str) -> Result<T, StoreError>
+    where
+        T: DeserializeOwned + Clone + Send + Sync + 'static,
+    {
+        let slot = self.slot(key);
+        let mut value = slot.value.lock().expect("StateCache slot poisoned");
+
+        Ok(Self::get_or_load::<T>(&slot, &mut value, store, key)?.clone())
+    }
+
+    /// Runs `f` on a copy of the state and only replaces the cached value once the store accepted it
+    pub async fn update<T, R, F>(
+        &self,
+        store: &impl StateStore,
+        key: &str,
+        f: F,
+    ) -> Result<R, StoreError>
+    where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        F: FnOnce(&mut T) -> R,
+    {
+        let slot = self.slot(key);
+        let _guard = slot.lock.lock().await;
+
+        let mut state = {
+            let mut value = slot.value.lock().expect("StateCache slot poisoned");
+            Self::get_or_load::<T>(&slot, &mut value, store, key)?.clone()
+        };
+        let result = f(&mut state);
+        store.save_versioned(key, &state)?;
+        *slot.value.lock().expect("StateCache slot poisoned") = Some(Box::new(state));
+
+        Ok(result)
+    }
+
+    /// Runs a raw write to `key` under its lock, then drops the cached value so it is reloaded
+    pub async fn write_with<R>(&self, key: &str, f: impl FnOnce() -> R) -> R {
+        let slot = self.slot(key);
+        let _guard = slot.lock.lock().await;
+
+        let result = f();
+        *slot.value.lock().expect("StateCache slot poisoned") = None;
+
+        result
+    }
+
+    pub fn stats(&self) -> Vec<CacheStats> {
+        let slots = self.0.lock().expect("StateCache mutex poisoned");
+
+        let mut stats: Vec<CacheStats> = slots
+            .iter()
+            .map(|(key, slot)| CacheStats {
+                key: key.clone(),
+                hits: slot.hits.load(Ordering::Relaxed),
+                misses: slot.misses.load(Ordering::Relaxed),
+            })
+            .collect();
+        stats.sort_by(|a, b| a.key.cmp(&b.key));
+
+        stats
+    }
+
+    fn slot(&self, key: &str) -> Arc<CacheSlot> {
+        let mut slots = self.0.lock().expect("StateCache mutex poisoned");
+        slots.entry(key.to_string()).or_default().clone()
+    }
+
+    fn get_or_load<'a, T>(
+        slot: &CacheSlot,
+        value: &'a mut Option<Box<dyn Any + Send + Sync>>,
+        store: &impl StateStore,
+        key: &str,
+    ) -> Result<&'a T, StoreError>
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        if value.as_ref().map(|x| x.is::<T>()).unwrap_or(false) {
+            slot.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            slot.misses.fetch_add(1, Ordering::Relaxed);
+            *value = Some(Box::new(store.load_versioned::<T>(key)?));
+        }
+
+        Ok(value
+            .as_ref()
+            .and_then(|x| x.downcast_ref::<T>())
+            .expect("StateCache slot holds the wrong type"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::store::MemoryStore;
+
+    #[test]
+    fn loads_are_served_from_memory_after_the_first_read() {
+        let store = MemoryStore::default();
+        let cache = StateCache::default();
+        store.save_versioned("games", vec![1u64]).unwrap();
+
+        assert_eq!(cache.load::<Vec<u64>>(&store, "games").unwrap(), vec![1]);
+
+        // Written behind the cache's back, so only a miss would see it
+        store.save_versioned("games", vec![2u64]).unwrap();
+        assert_eq!(cache.load::<Vec<u64>>(&store, "games").unwrap(), vec![1]);
+
+        let stats = cache.stats();
+        assert_eq!((stats[0].hits, stats[0].misses), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn updates_write_through_to_the_store() {
+        let store = MemoryStore::default();
+        let cache = StateCache::default();
+        store.save_versioned("games", vec![1u64]).unwrap();
+
+        let len = cache
+            .update(&store, "games", |games: &mut Vec<u64>| {
+                games.push(2);
+                games.len()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(len, 2);
+        assert_eq!(cache.load::<Vec<u64>>(&store, "games").unwrap(), vec![1, 2]);
+        assert_eq!(
+            store.load_versioned::<Vec<u64>>("games").unwrap(),
+            vec![1, 2]
+        );
+    }
+
+    #[tokio::test]
+    async fn unregistered_keys_are_never_cached() {
+        let store = MemoryStore::default();
+        let cache = StateCache::default();
+        store.save("not_registered", Vec::<u64>::new()).unwrap();
+
+        let result = cache
+            .update(&store, "not_registered", |x: &mut Vec<u64>| x.push(1))
+            .await;
+
+        assert!(result.is_err());
+        assert!(cache.load::<Vec<u64>>(&store, "not_registered").is_err());
+    }
+
+    #[tokio::test]
+    async fn raw_writes_drop_the_cached_value() {
+        let store = MemoryStore::default();
+        let cache = StateCache::default();
+        store.save_versioned("games", vec![1u64]).unwrap();
+        cache.load::<Vec<u64>>(&store, "games").unwrap();
+
+        cache
+            .write_with("games", || store.save_versioned("games", vec![3u64]))
+            .await
+            .unwrap();
+
+        assert_eq!(cache.load::<Vec<u64>>(&store, "games").unwrap(), vec![3]);
+    }
+}
@@ -0,0 +1,214 @@
+use super::history::{Origin, StateHistory};
+use crate::state::BotStateInitialization;
+use crate::Data;
+use chrono::Utc;
+use poise::serenity_prelude::{Role, RoleId};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+const KEY: &str = "game_activity";
+
+/// A player is written again at most this often per game, presence updates are very chatty
+const PLAYED_RESOLUTION_SECONDS: i64 = 3600;
+
+/// Least recently played games are dropped past this point, keeps the persisted blob small
+const MAX_GAMES: usize = 500;
+
+/// What members play according to their presence, and how that maps to game roles
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct GameActivity {
+    /// Normalized activity name -> game role, for games whose role is named differently
+    pub aliases: BTreeMap<String, u64>,
+    /// Members that asked for game role suggestions
+    pub opted_in: BTreeSet<u64>,
+    /// Every game seen being played, keyed by normalized activity name
+    pub played: BTreeMap<String, PlayedGame>,
+    /// `(user, role)` pairs that were already suggested, nobody is asked twice
+    pub suggested: BTreeSet<(u64, u64)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct PlayedGame {
+    /// Activity name as last reported by Discord
+    pub name: String,
+    /// Player -> unix timestamp in seconds of when they were last seen playing
+    pub players: BTreeMap<u64, i64>,
+    /// Unix timestamp in seconds
+    pub last_played: i64,
+}
+
+/// Activity and alias names are compared case and whitespace insensitive
+pub fn normalize(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+// Main functionality
+impl GameActivity {
+    /// Remembers that a member plays a game, returns false if that was already known recently
+    pub async fn played<U: Into<u64>>(
+        data: &Data,
+        user_id: U,
+        name: &str,
+    ) -> Result<bool, anyhow::Error> {
+        let user_id: u64 = user_id.into();
+        let key = normalize(name);
+        let now = Utc::now().timestamp();
+
+        let known = Self::load(data)?
+            .played
+            .get(&key)
+            .and_then(|x| x.players.get(&user_id))
+            .is_some_and(|x| now - x < PLAYED_RESOLUTION_SECONDS);
+        if known {
+            return Ok(false);
+        }
+
+        Self::update(data, |activity| {
+            let game = activity.played.entry(key).or_default();
+            game.name = name.to_string();
+            game.players.insert(user_id, now);
+            game.last_played = now;
+
+            while activity.played.len() > MAX_GAMES {
+                let oldest = activity
+                    .played
+                    .iter()
+                    .min_by_key(|(_, x)| x.last_played)
+                    .map(|(key, _)| key.clone());
+                match oldest {
+                    Some(oldest) => activity.played.remove(&oldest),
+                    None => break,
+                };
+            }
+        })
+        .await?;
+
+        Ok(true)
+    }
+
+    /// The game role an activity belongs to, an alias wins over a role with the same name
+    pub fn match_role(&self, name: &str, games: &[Role]) -> Option<RoleId> {
+        let name = normalize(name);
+
+        if let Some(role_id) = self.aliases.get(&name) {
+            return Some(RoleId(*role_id));
+        }
+
+        games
+            .iter()
+            .find(|x| normalize(&x.name) == name)
+            .map(|x| x.id)
+    }
+
+    pub async fn add_alias(
+        data: &Data,
+        origin: &Origin,
+        alias: &str,
+        role_id: RoleId,
+    ) -> Result<bool, anyhow::Error> {
+        let alias = normalize(alias);
+
+        let before = Self::update(data, |activity| {
+            activity.aliases.insert(alias.clone(), role_id.0)
+        })
+        .await?;
+
+        if before == Some(role_id.0) {
+            return Ok(false);
+        }
+
+        let entry = |role_id: Option<u64>| role_id.map(|x| BTreeMap::from([(alias.clone(), x)]));
+        StateHistory::record(data, origin, KEY, entry(before), entry(Some(role_id.0))).await?;
+
+        Ok(true)
+    }
+
+    pub async fn remove_alias(
+        data: &Data,
+        origin: &Origin,
+        alias: &str,
+    ) -> Result<bool, anyhow::Error> {
+        let alias = normalize(alias);
+
+        let before = Self::update(data, |activity| activity.aliases.remove(&alias)).await?;
+
+        let Some(before) = before else {
+            return Ok(false);
+        };
+
+        StateHistory::record(
+            data,
+            origin,
+            KEY,
+            Some(BTreeMap::from([(alias, before)])),
+            None::<()>,
+        )
+        .await?;
+
+        Ok(true)
+    }
+
+    /// Turns suggestions on or off for a member, returns false if nothing changed
+    pub async fn set_opt_in<U: Into<u64>>(
+        data: &Data,
+        user_id: U,
+        enabled: bool,
+    ) -> Result<bool, anyhow::Error> {
+        let user_id: u64 = user_id.into();
+
+        Self::update(data, |activity| match enabled {
+            true => activity.opted_in.insert(user_id),
+            false => activity.opted_in.remove(&user_id),
+        })
+        .await
+    }
+
+    /// Claims a suggestion for a member, returns false if they already got it
+    pub async fn claim_suggestion<U: Into<u64>>(
+        data: &Data,
+        user_id: U,
+        role_id: RoleId,
+    ) -> Result<bool, anyhow::Error> {
+        let user_id: u64 = user_id.into();
+
+        Self::update(data, |activity| {
+            activity.suggested.insert((user_id, role_id.0))
+        })
+        .await
+    }
+
+    /// Played games that match no game role, most players first
+    pub fn unregistered(&self, games: &[Role]) -> Vec<&PlayedGame> {
+        let mut unregistered: Vec<&PlayedGame> = self
+            .played
+            .values()
+            .filter(|x| self.match_role(&x.name, games).is_none())
+            .collect();
+
+        unregistered.sort_by_key(|x| std::cmp::Reverse(x.players.len()));
+        unregistered
+    }
+}
+
+// Core functionality
+impl GameActivity {
+    pub fn load(data: &Data) -> Result<Self, anyhow::Error>
+    where
+        for<'de> Self: Deserialize<'de>,
+    {
+        let data = data.state_cache.load::<Self>(&data.bot_state, KEY);
+        match data {
+            Ok(data) => Ok(data),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl BotStateInitialization for GameActivity {
+    fn get_key(&self) -> String {
+        KEY.to_string()
+    }
+}
@@ -1,4 +1,4 @@
-use super::{SnowflakeStorage, SnowflakesToRoles};
+use super::{SnowflakeSet, SnowflakeStorage, SnowflakesToRoles};
 use crate::Data;
 use crate::{extensions::InteractiveSnowflakeExt, state::BotStateInitialization};
 use poise::serenity_prelude::RoleId;
@@ -7,15 +7,12 @@ use serde::{Deserialize, Serialize};
 const KEY: &str = "games";
 
 #[derive(Serialize, Deserialize, Clone, Default)]
-pub struct Games(pub Vec<u64>);
+pub struct Games(pub SnowflakeSet<RoleId>);
 
 impl std::fmt::Display for Games {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let raw_games = &self.0;
-
         let mut games = String::new();
-        for game in raw_games {
-            let game = RoleId(*game);
+        for game in self.0.iter() {
             let game = game.get_interactive();
             games.push_str(&format!("{}\n", game));
         }
@@ -30,31 +27,25 @@ impl BotStateInitialization for Games {
 }
 
 impl SnowflakeStorage for Games {
+    type Id = RoleId;
+
     fn load(data: &Data) -> Result<Self, anyhow::Error>
     where
         for<'de> Self: Deserialize<'de>,
     {
-        let data = data.bot_state.load::<Self>(KEY);
+        let data = data.state_cache.load::<Self>(&data.bot_state, KEY);
         match data {
             Ok(data) => Ok(data),
-            Err(e) => Err(anyhow::anyhow!("{}", e)),
+            Err(e) => Err(e.into()),
         }
     }
 
-    fn snowflake_found(&self, id: &u64) -> bool {
-        self.0.contains(id)
-    }
-
-    fn push_snowflake(&mut self, id: u64) {
-        self.0.push(id);
-    }
-
-    fn snowflakes(&self) -> std::slice::Iter<'_, u64> {
-        self.0.iter()
+    fn snowflakes(&self) -> &SnowflakeSet<Self::Id> {
+        &self.0
     }
 
-    fn remove_snowflake(&mut self, index: usize) {
-        self.0.remove(index);
+    fn snowflakes_mut(&mut self) -> &mut SnowflakeSet<Self::Id> {
+        &mut self.0
     }
 }
 
@@ -22,14 +22,14 @@ impl SnowflakeHashmapStorage for GuildApply {
     where
         for<'de> Self: Deserialize<'de>,
     {
-        let data = data.bot_state.load::<Self>(KEY);
+        let data = data.state_cache.load::<Self>(&data.bot_state, KEY);
         match data {
             Ok(data) => Ok(data),
-            Err(e) => Err(anyhow::anyhow!("{}", e)),
+            Err(e) => Err(e.into()),
         }
     }
 
-    fn snowflake_key_found(&self, key: &String) -> bool {
+    fn snowflake_key_found(&self, key: &str) -> bool {
         self.0.contains_key(key)
     }
 
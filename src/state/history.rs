@@ -0,0 +1,122 @@
+use crate::state::BotStateInitialization;
+use crate::utils::embed::truncate_to;
+use crate::{Context, Data};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+const KEY: &str = "state_history";
+
+/// Oldest entries are dropped past this point, keeps the persisted blob small
+const MAX_ENTRIES: usize = 2000;
+
+/// Longer values are cut when shown, a whole state key can be stored as one value
+const VALUE_DISPLAY_LIMIT: usize = 300;
+
+/// Audit trail of every add / remove done on the state keys, oldest first
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct StateHistory(pub Vec<HistoryEntry>);
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HistoryEntry {
+    /// Unix timestamp in seconds
+    pub timestamp: i64,
+    pub actor: u64,
+    pub command: String,
+    pub key: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+/// Who triggered a state mutation and through which command
+pub struct Origin {
+    pub actor: u64,
+    pub command: String,
+}
+
+impl From<Context<'_>> for Origin {
+    fn from(ctx: Context<'_>) -> Self {
+        Self {
+            actor: ctx.author().id.0,
+            command: ctx.command().qualified_name.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for HistoryEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let show = |value: &Value| match value {
+            Value::Null => "none".to_string(),
+            value => format!("`{}`", truncate_to(&value.to_string(), VALUE_DISPLAY_LIMIT)),
+        };
+
+        write!(
+            f,
+            "<t:{}:f> <@{}> `/{}` on `{}`: {} -> {}",
+            self.timestamp,
+            self.actor,
+            self.command,
+            self.key,
+            show(&self.before),
+            show(&self.after)
+        )
+    }
+}
+
+// Main functionality
+impl StateHistory {
+    pub async fn record<B: Serialize, A: Serialize>(
+        data: &Data,
+        origin: &Origin,
+        key: &str,
+        before: B,
+        after: A,
+    ) -> Result<(), anyhow::Error> {
+        let entry = HistoryEntry {
+            timestamp: Utc::now().timestamp(),
+            actor: origin.actor,
+            command: origin.command.clone(),
+            key: key.to_string(),
+            before: serde_json::to_value(before)?,
+            after: serde_json::to_value(after)?,
+        };
+
+        Self::update(data, |history| {
+            history.0.push(entry);
+
+            let overflow = history.0.len().saturating_sub(MAX_ENTRIES);
+            history.0.drain(..overflow);
+        })
+        .await
+    }
+
+    /// Entries matching both filters, newest first
+    pub fn filter(&self, key: Option<&str>, actor: Option<u64>) -> Vec<&HistoryEntry> {
+        self.0
+            .iter()
+            .rev()
+            .filter(|x| key.map(|key| x.key == key).unwrap_or(true))
+            .filter(|x| actor.map(|actor| x.actor == actor).unwrap_or(true))
+            .collect()
+    }
+}
+
+// Core functionality
+impl StateHistory {
+    pub fn load(data: &Data) -> Result<Self, anyhow::Error>
+    where
+        for<'de> Self: Deserialize<'de>,
+    {
+        let data = data.state_cache.load::<Self>(&data.bot_state, KEY);
+        match data {
+            Ok(data) => Ok(data),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl BotStateInitialization for StateHistory {
+    fn get_key(&self) -> String {
+        KEY.to_string()
+    }
+}
@@ -0,0 +1,80 @@
+use crate::state::BotStateInitialization;
+use crate::Data;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const KEY: &str = "invite_joins";
+
+/// Oldest joins are dropped past this point, keeps the persisted blob small
+const MAX_ENTRIES: usize = 5000;
+
+/// Every member join and the invite it came through, oldest first
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct InviteJoins(pub Vec<InviteJoin>);
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InviteJoin {
+    /// Unix timestamp in seconds
+    pub timestamp: i64,
+    pub user_id: u64,
+    /// `None` when the invite could not be determined, e.g. a vanity url
+    pub code: Option<String>,
+    pub inviter: Option<u64>,
+}
+
+// Main functionality
+impl InviteJoins {
+    pub async fn record(
+        data: &Data,
+        user_id: u64,
+        code: Option<String>,
+        inviter: Option<u64>,
+    ) -> Result<(), anyhow::Error> {
+        let join = InviteJoin {
+            timestamp: Utc::now().timestamp(),
+            user_id,
+            code,
+            inviter,
+        };
+
+        Self::update(data, |joins| {
+            joins.0.push(join);
+
+            let overflow = joins.0.len().saturating_sub(MAX_ENTRIES);
+            joins.0.drain(..overflow);
+        })
+        .await
+    }
+
+    /// Joins per inviter from `since` on, unknown inviters are counted under `None`
+    pub fn per_inviter(&self, since: i64) -> HashMap<Option<u64>, usize> {
+        let mut counts = HashMap::new();
+
+        for join in self.0.iter().filter(|x| x.timestamp >= since) {
+            *counts.entry(join.inviter).or_default() += 1;
+        }
+
+        counts
+    }
+}
+
+// Core functionality
+impl InviteJoins {
+    pub fn load(data: &Data) -> Result<Self, anyhow::Error>
+    where
+        for<'de> Self: Deserialize<'de>,
+    {
+        let data = data.state_cache.load::<Self>(&data.bot_state, KEY);
+        match data {
+            Ok(data) => Ok(data),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl BotStateInitialization for InviteJoins {
+    fn get_key(&self) -> String {
+        KEY.to_string()
+    }
+}
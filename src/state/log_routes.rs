@@ -0,0 +1,375 @@
+use super::history::{Origin, StateHistory};
+use crate::error::channel_from_secret;
+use crate::extensions::InteractiveSnowflakeExt;
+use crate::state::BotStateInitialization;
+use crate::utils::embed::truncate;
+use crate::Data;
+use poise::serenity_prelude::ChannelId;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const KEY: &str = "log_routes";
+
+/// Every kind of event that can be posted to a log channel.
+/// Commands take it as an autocompleted name, there are more kinds than Discord allows choices
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    poise::ChoiceParameter,
+)]
+pub enum LogKind {
+    #[name = "Member Joined"]
+    UserJoin,
+    #[name = "Member Left"]
+    UserLeave,
+    #[name = "Member Banned"]
+    UserBan,
+    #[name = "Member Unbanned"]
+    UserUnban,
+    #[name = "Roles Updated"]
+    RoleChange,
+    #[name = "Nickname Changed"]
+    NicknameChange,
+    #[name = "Member Timed Out"]
+    MemberTimeout,
+    #[name = "Server Avatar Changed"]
+    GuildAvatarChange,
+    #[name = "Server Boost"]
+    BoostChange,
+    #[name = "Membership Screening"]
+    MembershipScreening,
+    #[name = "Channel Created"]
+    ChannelCreate,
+    #[name = "Channel Updated"]
+    ChannelUpdate,
+    #[name = "Channel Deleted"]
+    ChannelDelete,
+    #[name = "Role Created"]
+    RoleCreate,
+    #[name = "Role Updated"]
+    RoleUpdate,
+    #[name = "Role Deleted"]
+    RoleDelete,
+    #[name = "Joined Voice Chat"]
+    VoiceJoin,
+    #[name = "Left Voice Chat"]
+    VoiceLeave,
+    #[name = "Moved Voice Chat"]
+    VoiceMove,
+    #[name = "Self Mute"]
+    VoiceSelfMute,
+    #[name = "Self Deafen"]
+    VoiceSelfDeafen,
+    #[name = "Server Mute"]
+    VoiceServerMute,
+    #[name = "Server Deafen"]
+    VoiceServerDeafen,
+    #[name = "Streaming"]
+    VoiceStream,
+    #[name = "Camera"]
+    VoiceVideo,
+    #[name = "Stage Speaker"]
+    VoiceSuppress,
+    #[name = "Message Edited"]
+    MessageEdit,
+    #[name = "Message Deleted"]
+    MessageDelete,
+    #[name = "Thread Created"]
+    ThreadCreate,
+    #[name = "Thread Archived"]
+    ThreadArchive,
+    #[name = "Thread Locked"]
+    ThreadLock,
+    #[name = "Thread Deleted"]
+    ThreadDelete,
+    #[name = "Joined Thread"]
+    ThreadMemberJoin,
+    #[name = "Left Thread"]
+    ThreadMemberLeave,
+}
+
+impl LogKind {
+    pub const ALL: [LogKind; 34] = [
+        Self::UserJoin,
+        Self::UserLeave,
+        Self::UserBan,
+        Self::UserUnban,
+        Self::RoleChange,
+        Self::NicknameChange,
+        Self::MemberTimeout,
+        Self::GuildAvatarChange,
+        Self::BoostChange,
+        Self::MembershipScreening,
+        Self::ChannelCreate,
+        Self::ChannelUpdate,
+        Self::ChannelDelete,
+        Self::RoleCreate,
+        Self::RoleUpdate,
+        Self::RoleDelete,
+        Self::VoiceJoin,
+        Self::VoiceLeave,
+        Self::VoiceMove,
+        Self::VoiceSelfMute,
+        Self::VoiceSelfDeafen,
+        Self::VoiceServerMute,
+        Self::VoiceServerDeafen,
+        Self::VoiceStream,
+        Self::VoiceVideo,
+        Self::VoiceSuppress,
+        Self::MessageEdit,
+        Self::MessageDelete,
+        Self::ThreadCreate,
+        Self::ThreadArchive,
+        Self::ThreadLock,
+        Self::ThreadDelete,
+        Self::ThreadMemberJoin,
+        Self::ThreadMemberLeave,
+    ];
+
+    /// Heading the event is listed under by `/list_log_routes`
+    pub fn group(&self) -> &'static str {
+        match self {
+            Self::UserJoin
+            | Self::UserLeave
+            | Self::UserBan
+            | Self::UserUnban
+            | Self::RoleChange
+            | Self::NicknameChange
+            | Self::MemberTimeout
+            | Self::GuildAvatarChange
+            | Self::BoostChange
+            | Self::MembershipScreening => "Members",
+            Self::ChannelCreate
+            | Self::ChannelUpdate
+            | Self::ChannelDelete
+            | Self::RoleCreate
+            | Self::RoleUpdate
+            | Self::RoleDelete => "Channels and Roles",
+            Self::VoiceJoin
+            | Self::VoiceLeave
+            | Self::VoiceMove
+            | Self::VoiceSelfMute
+            | Self::VoiceSelfDeafen
+            | Self::VoiceServerMute
+            | Self::VoiceServerDeafen
+            | Self::VoiceStream
+            | Self::VoiceVideo
+            | Self::VoiceSuppress => "Voice Chat",
+            Self::MessageEdit | Self::MessageDelete => "Messages",
+            Self::ThreadCreate
+            | Self::ThreadArchive
+            | Self::ThreadLock
+            | Self::ThreadDelete
+            | Self::ThreadMemberJoin
+            | Self::ThreadMemberLeave => "Threads",
+        }
+    }
+
+    /// Where this event goes while it has no entry in the routing table
+    pub fn default_channel(&self, data: &Data) -> Result<ChannelId, crate::Error> {
+        match self {
+            Self::UserJoin
+            | Self::UserLeave
+            | Self::UserBan
+            | Self::UserUnban
+            | Self::RoleChange
+            | Self::NicknameChange
+            | Self::MemberTimeout
+            | Self::BoostChange
+            | Self::ChannelCreate
+            | Self::ChannelUpdate
+            | Self::ChannelDelete
+            | Self::RoleCreate
+            | Self::RoleUpdate
+            | Self::RoleDelete
+            | Self::VoiceServerMute
+            | Self::VoiceServerDeafen => {
+                channel_from_secret(&data.major_events_channel, "MAJOR_EVENTS_CHANNEL")
+            }
+            Self::GuildAvatarChange
+            | Self::MembershipScreening
+            | Self::VoiceJoin
+            | Self::VoiceLeave
+            | Self::VoiceMove
+            | Self::VoiceSelfMute
+            | Self::VoiceSelfDeafen
+            | Self::VoiceStream
+            | Self::VoiceVideo
+            | Self::VoiceSuppress
+            | Self::MessageEdit
+            | Self::MessageDelete
+            | Self::ThreadCreate
+            | Self::ThreadArchive
+            | Self::ThreadLock
+            | Self::ThreadDelete
+            | Self::ThreadMemberJoin
+            | Self::ThreadMemberLeave => {
+                channel_from_secret(&data.minor_events_channel, "MINOR_EVENTS_CHANNEL")
+            }
+        }
+    }
+}
+
+/// Channels each event kind is posted to. Kinds without an entry use their default channel,
+/// an entry with no channels turns the event off
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct LogRoutes(pub BTreeMap<LogKind, Vec<u64>>);
+
+// Main functionality
+impl LogRoutes {
+    pub fn targets(data: &Data, kind: LogKind) -> Result<Vec<ChannelId>, crate::Error> {
+        let routes = Self::load(data)?;
+
+        match routes.0.get(&kind) {
+            Some(channels) => Ok(channels.iter().map(|x| ChannelId(*x)).collect()),
+            None => Ok(vec![kind.default_channel(data)?]),
+        }
+    }
+
+    /// Adds a channel to the event, the first change starts from its default channel
+    pub async fn add(
+        data: &Data,
+        origin: &Origin,
+        kind: LogKind,
+        channel_id: ChannelId,
+    ) -> Result<bool, crate::Error> {
+        let default = kind.default_channel(data)?.0;
+
+        Self::edit(data, origin, kind, |channels| {
+            let channels = channels.get_or_insert_with(|| vec![default]);
+            if channels.contains(&channel_id.0) {
+                return false;
+            }
+
+            channels.push(channel_id.0);
+            true
+        })
+        .await
+    }
+
+    /// Removes a single channel from the event, or turns it off entirely when `channel_id` is `None`
+    pub async fn remove(
+        data: &Data,
+        origin: &Origin,
+        kind: LogKind,
+        channel_id: Option<ChannelId>,
+    ) -> Result<bool, crate::Error> {
+        let default = kind.default_channel(data)?.0;
+
+        Self::edit(data, origin, kind, |channels| {
+            let channels = channels.get_or_insert_with(|| vec![default]);
+            let before = channels.len();
+
+            match channel_id {
+                Some(channel_id) => channels.retain(|x| *x != channel_id.0),
+                None => channels.clear(),
+            }
+
+            channels.len() != before
+        })
+        .await
+    }
+
+    /// Drops the event's entry, sending it to its default channel again
+    pub async fn reset(data: &Data, origin: &Origin, kind: LogKind) -> Result<bool, crate::Error> {
+        Self::edit(data, origin, kind, |channels| channels.take().is_some()).await
+    }
+
+    /// Runs `f` on the event's entry and records the change when `f` returns true
+    async fn edit<F>(
+        data: &Data,
+        origin: &Origin,
+        kind: LogKind,
+        f: F,
+    ) -> Result<bool, crate::Error>
+    where
+        F: FnOnce(&mut Option<Vec<u64>>) -> bool,
+    {
+        let (changed, before, after) = Self::update(data, |routes| {
+            let before = routes.0.get(&kind).cloned();
+            let mut channels = before.clone();
+            let changed = f(&mut channels);
+
+            if changed {
+                match &channels {
+                    Some(channels) => routes.0.insert(kind, channels.clone()),
+                    None => routes.0.remove(&kind),
+                };
+            }
+
+            (changed, before, channels)
+        })
+        .await?;
+
+        if changed {
+            let entry = |channels: Option<Vec<u64>>| channels.map(|x| BTreeMap::from([(kind, x)]));
+            StateHistory::record(data, origin, KEY, entry(before), entry(after)).await?;
+        }
+
+        Ok(changed)
+    }
+
+    /// One embed field per group of events, with a line per event listing where it is posted
+    pub fn describe(&self, data: &Data) -> Result<Vec<(&'static str, String)>, crate::Error> {
+        let mut groups: Vec<(&'static str, String)> = vec![];
+
+        for kind in LogKind::ALL {
+            let channels = match self.0.get(&kind) {
+                Some(channels) if channels.is_empty() => "*Disabled*".to_string(),
+                Some(channels) => channels
+                    .iter()
+                    .map(|x| ChannelId(*x).get_interactive())
+                    .collect::<Vec<String>>()
+                    .join(" "),
+                None => format!(
+                    "{} *(default)*",
+                    kind.default_channel(data)?.get_interactive()
+                ),
+            };
+            let line = format!("**{}**: {}\n", kind.name(), channels);
+
+            // `ALL` lists the events of a group next to each other
+            match groups.last_mut() {
+                Some((group, lines)) if *group == kind.group() => lines.push_str(&line),
+                _ => groups.push((kind.group(), line)),
+            }
+        }
+
+        Ok(groups
+            .into_iter()
+            .map(|(group, lines)| (group, truncate(&lines)))
+            .collect())
+    }
+
+    /// Whether any event is routed to the channel
+    pub fn routes_to(&self, channel_id: u64) -> bool {
+        self.0.values().any(|x| x.contains(&channel_id))
+    }
+}
+
+// Core functionality
+impl LogRoutes {
+    pub fn load(data: &Data) -> Result<Self, anyhow::Error>
+    where
+        for<'de> Self: Deserialize<'de>,
+    {
+        let data = data.state_cache.load::<Self>(&data.bot_state, KEY);
+        match data {
+            Ok(data) => Ok(data),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl BotStateInitialization for LogRoutes {
+    fn get_key(&self) -> String {
+        KEY.to_string()
+    }
+}
@@ -0,0 +1,224 @@
+use crate::state::BotStateInitialization;
+use crate::Data;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const KEY: &str = "member_history";
+
+/// Oldest entries of a member are dropped past this point, keeps the persisted blob small
+const MAX_ENTRIES_PER_USER: usize = 200;
+
+/// Oldest entries of anyone are dropped past this point, members who left long ago add up
+const MAX_ENTRIES: usize = 20000;
+
+/// Membership timeline of every member the bot has seen, oldest entry first
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct MemberHistory(pub BTreeMap<u64, Vec<MemberHistoryEntry>>);
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MemberHistoryEntry {
+    /// Unix timestamp in seconds
+    pub timestamp: i64,
+    pub event: MemberHistoryEvent,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum MemberHistoryEvent {
+    Joined {
+        invite: Option<String>,
+        inviter: Option<u64>,
+    },
+    Left {
+        roles: Vec<u64>,
+    },
+    Banned {
+        moderator: Option<u64>,
+        reason: Option<String>,
+    },
+    Unbanned,
+    NicknameChanged {
+        old: Option<String>,
+        new: Option<String>,
+        moderator: Option<u64>,
+    },
+    RolesChanged {
+        added: Vec<u64>,
+        removed: Vec<u64>,
+        moderator: Option<u64>,
+    },
+}
+
+impl std::fmt::Display for MemberHistoryEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let roles = |ids: &[u64]| {
+            ids.iter()
+                .map(|x| format!("<@&{}>", x))
+                .collect::<Vec<String>>()
+                .join(" ")
+        };
+        let nickname = |x: &Option<String>| match x {
+            Some(x) => format!("`{}`", x),
+            None => "*Default Nickname*".to_string(),
+        };
+        let by = |x: &Option<u64>| match x {
+            Some(x) => format!(" by <@{}>", x),
+            None => String::new(),
+        };
+
+        let description = match &self.event {
+            MemberHistoryEvent::Joined { invite, inviter } => match (invite, inviter) {
+                (Some(invite), Some(inviter)) => {
+                    format!("Joined via `{}`, invited by <@{}>", invite, inviter)
+                }
+                (Some(invite), None) => format!("Joined via `{}`", invite),
+                _ => "Joined".to_string(),
+            },
+            MemberHistoryEvent::Left { roles: ids } if ids.is_empty() => "Left".to_string(),
+            MemberHistoryEvent::Left { roles: ids } => format!("Left with {}", roles(ids)),
+            MemberHistoryEvent::Banned { moderator, reason } => format!(
+                "Banned{}{}",
+                by(moderator),
+                reason
+                    .as_ref()
+                    .map(|x| format!(": {}", x))
+                    .unwrap_or_default()
+            ),
+            MemberHistoryEvent::Unbanned => "Unbanned".to_string(),
+            MemberHistoryEvent::NicknameChanged {
+                old,
+                new,
+                moderator,
+            } => format!(
+                "Nickname {} -> {}{}",
+                nickname(old),
+                nickname(new),
+                by(moderator)
+            ),
+            MemberHistoryEvent::RolesChanged {
+                added,
+                removed,
+                moderator,
+            } => {
+                let mut changes = vec![];
+                if !added.is_empty() {
+                    changes.push(format!("+{}", roles(added)));
+                }
+                if !removed.is_empty() {
+                    changes.push(format!("-{}", roles(removed)));
+                }
+                format!("Roles {}{}", changes.join(" "), by(moderator))
+            }
+        };
+
+        write!(f, "<t:{}:f> {}", self.timestamp, description)
+    }
+}
+
+// Main functionality
+impl MemberHistory {
+    pub async fn record<U: Into<u64>>(
+        data: &Data,
+        user_id: U,
+        event: MemberHistoryEvent,
+    ) -> Result<(), anyhow::Error> {
+        let entry = MemberHistoryEntry {
+            timestamp: Utc::now().timestamp(),
+            event,
+        };
+
+        Self::update(data, |history| {
+            let entries = history.0.entry(user_id.into()).or_default();
+            entries.push(entry);
+
+            let overflow = entries.len().saturating_sub(MAX_ENTRIES_PER_USER);
+            entries.drain(..overflow);
+
+            history.prune(MAX_ENTRIES);
+        })
+        .await
+    }
+
+    /// Drops the oldest entries until at most `max` are left, and members left without any
+    fn prune(&mut self, max: usize) {
+        let mut total: usize = self.0.values().map(Vec::len).sum();
+
+        while total > max {
+            let oldest = self
+                .0
+                .iter()
+                .min_by_key(|(_, entries)| entries.first().map(|x| x.timestamp))
+                .map(|(user_id, _)| *user_id);
+            let Some(user_id) = oldest else {
+                break;
+            };
+
+            let entries = self.0.entry(user_id).or_default();
+            if !entries.is_empty() {
+                entries.remove(0);
+                total -= 1;
+            }
+            if entries.is_empty() {
+                self.0.remove(&user_id);
+            }
+        }
+    }
+
+    /// Timeline of one member, newest first
+    pub fn for_user<U: Into<u64>>(&self, user_id: U) -> Vec<&MemberHistoryEntry> {
+        self.0
+            .get(&user_id.into())
+            .map(|entries| entries.iter().rev().collect())
+            .unwrap_or_default()
+    }
+}
+
+// Core functionality
+impl MemberHistory {
+    pub fn load(data: &Data) -> Result<Self, anyhow::Error>
+    where
+        for<'de> Self: Deserialize<'de>,
+    {
+        let data = data.state_cache.load::<Self>(&data.bot_state, KEY);
+        match data {
+            Ok(data) => Ok(data),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl BotStateInitialization for MemberHistory {
+    fn get_key(&self) -> String {
+        KEY.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: i64) -> MemberHistoryEntry {
+        MemberHistoryEntry {
+            timestamp,
+            event: MemberHistoryEvent::Unbanned,
+        }
+    }
+
+    #[test]
+    fn prune_drops_the_oldest_entries_of_anyone() {
+        let mut history = MemberHistory(BTreeMap::from([
+            (1, vec![entry(10), entry(40)]),
+            (2, vec![entry(20)]),
+            (3, vec![entry(30), entry(50)]),
+        ]));
+
+        history.prune(3);
+
+        let timestamps = |user_id: u64| -> Vec<i64> {
+            history.0[&user_id].iter().map(|x| x.timestamp).collect()
+        };
+        assert_eq!(timestamps(1), vec![40]);
+        assert!(!history.0.contains_key(&2));
+        assert_eq!(timestamps(3), vec![30, 50]);
+    }
+}
@@ -0,0 +1,116 @@
+use crate::state::BotStateInitialization;
+use crate::Data;
+use chrono::Utc;
+use poise::serenity_prelude::Member;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const KEY: &str = "member_snapshots";
+
+/// Activity closer together than this is not written again, keeps busy channels from
+/// persisting the whole map on every message
+const LAST_SEEN_RESOLUTION_SECONDS: i64 = 300;
+
+/// Last known state of every current member, used when a member leaves without being cached
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct MemberSnapshots(pub BTreeMap<u64, MemberSnapshot>);
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct MemberSnapshot {
+    pub roles: Vec<u64>,
+    /// Unix timestamp in seconds
+    pub joined_at: Option<i64>,
+    /// Unix timestamp in seconds of the last message or voice join
+    pub last_seen: Option<i64>,
+}
+
+// Main functionality
+impl MemberSnapshots {
+    /// Stores the roles and join date of members, keeping their last activity.
+    /// Nothing is written unless one of them changed, member updates are frequent
+    pub async fn capture<'a, I>(data: &Data, members: I) -> Result<(), anyhow::Error>
+    where
+        I: IntoIterator<Item = &'a Member>,
+    {
+        let current = Self::load(data)?;
+
+        let changed: Vec<(u64, Vec<u64>, Option<i64>)> = members
+            .into_iter()
+            .map(|member| {
+                let mut roles: Vec<u64> = member.roles.iter().map(|x| x.0).collect();
+                roles.sort_unstable();
+
+                (
+                    member.user.id.0,
+                    roles,
+                    member.joined_at.map(|x| x.unix_timestamp()),
+                )
+            })
+            .filter(|(user_id, roles, joined_at)| {
+                current
+                    .0
+                    .get(user_id)
+                    .map(|x| x.roles != *roles || x.joined_at != *joined_at)
+                    .unwrap_or(true)
+            })
+            .collect();
+
+        if changed.is_empty() {
+            return Ok(());
+        }
+
+        Self::update(data, |snapshots| {
+            for (user_id, roles, joined_at) in changed {
+                let snapshot = snapshots.0.entry(user_id).or_default();
+                snapshot.roles = roles;
+                snapshot.joined_at = joined_at;
+            }
+        })
+        .await
+    }
+
+    pub async fn seen<U: Into<u64>>(data: &Data, user_id: U) -> Result<(), anyhow::Error> {
+        let user_id: u64 = user_id.into();
+        let now = Utc::now().timestamp();
+
+        let last_seen = Self::load(data)?.0.get(&user_id).and_then(|x| x.last_seen);
+        if last_seen.is_some_and(|x| now - x < LAST_SEEN_RESOLUTION_SECONDS) {
+            return Ok(());
+        }
+
+        Self::update(data, |snapshots| {
+            snapshots.0.entry(user_id).or_default().last_seen = Some(now);
+        })
+        .await
+    }
+
+    /// Removes the snapshot of a member that left and hands it back
+    pub async fn take<U: Into<u64>>(
+        data: &Data,
+        user_id: U,
+    ) -> Result<Option<MemberSnapshot>, anyhow::Error> {
+        let user_id: u64 = user_id.into();
+
+        Self::update(data, |snapshots| snapshots.0.remove(&user_id)).await
+    }
+}
+
+// Core functionality
+impl MemberSnapshots {
+    pub fn load(data: &Data) -> Result<Self, anyhow::Error>
+    where
+        for<'de> Self: Deserialize<'de>,
+    {
+        let data = data.state_cache.load::<Self>(&data.bot_state, KEY);
+        match data {
+            Ok(data) => Ok(data),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl BotStateInitialization for MemberSnapshots {
+    fn get_key(&self) -> String {
+        KEY.to_string()
+    }
+}
@@ -0,0 +1,488 @@
+use super::{
+    active_collectors::ActiveCollectors, admins::Admins, game_activity::GameActivity, games::Games,
+    guild_apply::GuildApply, history::StateHistory, invite_joins::InviteJoins,
+    log_routes::LogRoutes, member_history::MemberHistory, member_snapshots::MemberSnapshots,
+    quarantine::Quarantine, role_backups::RoleBackups, sink_filters::SinkFilters, t_rooms::TRooms,
+    voice_stats::VoiceStats, StateBackend, StateStore, StoreError,
+};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+const ENVELOPE_MAGIC: u64 = u64::from_be_bytes(*b"mythicst");
+
+/// Wrapper every state key is persisted in.
+///
+/// The payload is the state struct as JSON text, since bincode (used by shuttle_persist)
+/// can't store a `serde_json::Value`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StateEnvelope {
+    magic: Magic,
+    pub version: u32,
+    payload: String,
+}
+
+impl StateEnvelope {
+    pub fn new(version: u32, payload: &Value) -> Self {
+        Self {
+            magic: Magic,
+            version,
+            payload: payload.to_string(),
+        }
+    }
+
+    pub fn payload(&self) -> Result<Value, StoreError> {
+        serde_json::from_str(&self.payload).map_err(|e| StoreError::Deserialize(e.to_string()))
+    }
+}
+
+/// Fixed width marker at the start of every envelope.
+///
+/// Legacy data is rejected on this first field, before bincode reads any length prefix
+/// out of bytes that were never meant as one
+#[derive(Clone, Copy, Debug)]
+struct Magic;
+
+impl Serialize for Magic {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(ENVELOPE_MAGIC)
+    }
+}
+
+impl<'de> Deserialize<'de> for Magic {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match u64::deserialize(deserializer)? {
+            ENVELOPE_MAGIC => Ok(Magic),
+            _ => Err(serde::de::Error::custom("not a state envelope")),
+        }
+    }
+}
+
+/// A single upgrade step of a key's payload, from `from` to `from + 1`
+pub struct Migration {
+    pub from: u32,
+    pub description: &'static str,
+    pub run: fn(Value) -> Result<Value, anyhow::Error>,
+}
+
+pub struct KeySchema {
+    pub key: &'static str,
+    pub version: u32,
+    /// Reads the pre-envelope (version 0) data of this key.
+    /// Must keep pointing at the original struct shape, even after the struct itself changes
+    pub load_legacy: fn(&StateBackend) -> Result<Value, StoreError>,
+    /// Checks that a payload at the current version deserializes into the state struct
+    pub validate: fn(&Value) -> Result<(), serde_json::Error>,
+    pub migrations: &'static [Migration],
+}
+
+/// Every persisted key, its current schema version and the steps to get there.
+///
+/// Bump `version` and push a `Migration` whenever the shape of a state struct changes
+pub fn registry() -> Vec<KeySchema> {
+    vec![
+        KeySchema {
+            key: "admins",
+            version: 1,
+            load_legacy: |store| legacy::<Admins>(store, "admins"),
+            validate: validate::<Admins>,
+            migrations: &[],
+        },
+        KeySchema {
+            key: "games",
+            version: 1,
+            load_legacy: |store| legacy::<Games>(store, "games"),
+            validate: validate::<Games>,
+            migrations: &[],
+        },
+        KeySchema {
+            key: "active_collectors",
+            version: 2,
+            load_legacy: |store| legacy::<Vec<u64>>(store, "active_collectors"),
+            validate: validate::<ActiveCollectors>,
+            migrations: &[Migration {
+                from: 1,
+                description: "replaced the unused collector ids with an empty session registry",
+                run: |_| Ok(Value::Array(vec![])),
+            }],
+        },
+        KeySchema {
+            key: "role_backup",
+            version: 1,
+            load_legacy: |store| legacy::<RoleBackups>(store, "role_backup"),
+            validate: validate::<RoleBackups>,
+            migrations: &[],
+        },
+        KeySchema {
+            key: "t_rooms",
+            version: 1,
+            load_legacy: |store| legacy::<TRooms>(store, "t_rooms"),
+            validate: validate::<TRooms>,
+            migrations: &[],
+        },
+        KeySchema {
+            key: "guild_apply",
+            version: 1,
+            load_legacy: |store| legacy::<GuildApply>(store, "guild_apply"),
+            validate: validate::<GuildApply>,
+            migrations: &[],
+        },
+        KeySchema {
+            key: "state_history",
+            version: 1,
+            load_legacy: |store| legacy::<StateHistory>(store, "state_history"),
+            validate: validate::<StateHistory>,
+            migrations: &[],
+        },
+        KeySchema {
+            key: "log_routes",
+            version: 1,
+            load_legacy: |store| legacy::<LogRoutes>(store, "log_routes"),
+            validate: validate::<LogRoutes>,
+            migrations: &[],
+        },
+        KeySchema {
+            key: "voice_stats",
+            version: 1,
+            load_legacy: |store| legacy::<VoiceStats>(store, "voice_stats"),
+            validate: validate::<VoiceStats>,
+            migrations: &[],
+        },
+        KeySchema {
+            key: "invite_joins",
+            version: 1,
+            load_legacy: |store| legacy::<InviteJoins>(store, "invite_joins"),
+            validate: validate::<InviteJoins>,
+            migrations: &[],
+        },
+        KeySchema {
+            key: "quarantine",
+            version: 1,
+            load_legacy: |store| legacy::<Quarantine>(store, "quarantine"),
+            validate: validate::<Quarantine>,
+            migrations: &[],
+        },
+        KeySchema {
+            key: "sink_filters",
+            version: 1,
+            load_legacy: |store| legacy::<SinkFilters>(store, "sink_filters"),
+            validate: validate::<SinkFilters>,
+            migrations: &[],
+        },
+        KeySchema {
+            key: "member_history",
+            version: 1,
+            load_legacy: |store| legacy::<MemberHistory>(store, "member_history"),
+            validate: validate::<MemberHistory>,
+            migrations: &[],
+        },
+        KeySchema {
+            key: "member_snapshots",
+            version: 1,
+            load_legacy: |store| legacy::<MemberSnapshots>(store, "member_snapshots"),
+            validate: validate::<MemberSnapshots>,
+            migrations: &[],
+        },
+        KeySchema {
+            key: "game_activity",
+            version: 1,
+            load_legacy: |store| legacy::<GameActivity>(store, "game_activity"),
+            validate: validate::<GameActivity>,
+            migrations: &[],
+        },
+    ]
+}
+
+pub fn schema(key: &str) -> Option<KeySchema> {
+    registry().into_iter().find(|schema| schema.key == key)
+}
+
+pub fn current_version(key: &str) -> Option<u32> {
+    schema(key).map(|schema| schema.version)
+}
+
+/// Upgrades every registered key to its current version, logging each step taken
+pub fn run_all(store: &StateBackend) -> Result<(), anyhow::Error> {
+    for schema in registry() {
+        let (version, value) = match store.load::<StateEnvelope>(schema.key) {
+            Ok(envelope) => (envelope.version, envelope.payload()?),
+            // Anything that doesn't parse as an envelope is data written before versioning existed
+            Err(StoreError::Deserialize(_)) => (0, (schema.load_legacy)(store)?),
+            Err(StoreError::NotFound(_)) => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        if version == schema.version {
+            continue;
+        }
+
+        if version > schema.version {
+            return Err(anyhow::anyhow!(
+                "State key '{}' is at version {}, but this build only knows up to version {}",
+                schema.key,
+                version,
+                schema.version
+            ));
+        }
+
+        let value = migrate(&schema, version, value)?;
+        store.save(schema.key, StateEnvelope::new(schema.version, &value))?;
+    }
+
+    Ok(())
+}
+
+pub fn migrate(schema: &KeySchema, from: u32, mut value: Value) -> Result<Value, anyhow::Error> {
+    let mut version = from;
+
+    if version == 0 {
+        version = 1;
+        tracing::info!(
+            "State migration '{}': v0 -> v1, wrapped legacy data in a versioned envelope",
+            schema.key
+        );
+    }
+
+    while version < schema.version {
+        let step = schema
+            .migrations
+            .iter()
+            .find(|step| step.from == version)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No migration registered for state key '{}' from version {}",
+                    schema.key,
+                    version
+                )
+            })?;
+
+        value = (step.run)(value)?;
+        tracing::info!(
+            "State migration '{}': v{} -> v{}, {}",
+            schema.key,
+            version,
+            version + 1,
+            step.description
+        );
+        version += 1;
+    }
+
+    Ok(value)
+}
+
+/// Legacy data was stored as the bare struct, so it has to be read back with its original type
+fn legacy<T: DeserializeOwned + Serialize>(
+    store: &StateBackend,
+    key: &str,
+) -> Result<Value, StoreError> {
+    let value = store.load::<T>(key)?;
+    serde_json::to_value(value).map_err(|e| StoreError::Serialize(e.to_string()))
+}
+
+fn validate<T: DeserializeOwned>(value: &Value) -> Result<(), serde_json::Error> {
+    T::deserialize(value).map(|_| ())
+}
+
+/// A key missing from the registry would never be migrated or backed up, so it is refused
+fn registered_version(key: &str) -> Result<u32, StoreError> {
+    current_version(key).ok_or_else(|| StoreError::Unregistered(key.to_string()))
+}
+
+/// Loads and saves state wrapped in a `StateEnvelope` at the key's current version
+pub trait VersionedStateExt: StateStore {
+    fn load_versioned<T: DeserializeOwned>(&self, key: &str) -> Result<T, StoreError> {
+        let expected = registered_version(key)?;
+        let envelope = self.load::<StateEnvelope>(key)?;
+
+        if envelope.version != expected {
+            return Err(StoreError::Version {
+                key: key.to_string(),
+                found: envelope.version,
+                expected,
+            });
+        }
+
+        serde_json::from_str(&envelope.payload).map_err(|e| StoreError::Deserialize(e.to_string()))
+    }
+
+    fn save_versioned<T: Serialize>(&self, key: &str, value: T) -> Result<(), StoreError> {
+        let value =
+            serde_json::to_value(value).map_err(|e| StoreError::Serialize(e.to_string()))?;
+        let version = registered_version(key)?;
+
+        self.save(key, StateEnvelope::new(version, &value))
+    }
+}
+
+impl<S: StateStore> VersionedStateExt for S {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::store::MemoryStore;
+    use poise::serenity_prelude::RoleId;
+    use shuttle_persist::PersistInstance;
+
+    /// shuttle_persist stores everything with bincode, unlike the JSON based stores
+    fn bincode_store(name: &str) -> (StateBackend, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!(
+            "state-migrations-test-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let store = StateBackend::Shuttle(PersistInstance::new(dir.clone()).unwrap());
+
+        (store, dir)
+    }
+
+    fn games(ids: &[u64]) -> Games {
+        Games(ids.iter().map(|x| RoleId(*x)).collect())
+    }
+
+    fn role_ids(games: &Games) -> Vec<u64> {
+        games.0.iter().map(|x| x.0).collect()
+    }
+
+    #[test]
+    fn versioned_state_round_trips() {
+        let store = MemoryStore::default();
+
+        store.save_versioned("games", vec![1u64, 2, 3]).unwrap();
+
+        let envelope = store.load::<StateEnvelope>("games").unwrap();
+        assert_eq!(envelope.version, current_version("games").unwrap());
+        assert_eq!(envelope.payload().unwrap(), serde_json::json!([1, 2, 3]));
+        assert_eq!(
+            store.load_versioned::<Vec<u64>>("games").unwrap(),
+            vec![1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn unregistered_keys_are_refused() {
+        let store = MemoryStore::default();
+
+        assert!(matches!(
+            store.save_versioned("not_registered", 1u64),
+            Err(StoreError::Unregistered(_))
+        ));
+
+        store
+            .save("not_registered", StateEnvelope::new(1, &Value::from(1)))
+            .unwrap();
+        assert!(matches!(
+            store.load_versioned::<u64>("not_registered"),
+            Err(StoreError::Unregistered(_))
+        ));
+    }
+
+    #[test]
+    fn outdated_envelopes_are_refused() {
+        let store = MemoryStore::default();
+
+        store
+            .save(
+                "active_collectors",
+                StateEnvelope::new(1, &Value::Array(vec![])),
+            )
+            .unwrap();
+
+        assert!(matches!(
+            store.load_versioned::<Vec<u64>>("active_collectors"),
+            Err(StoreError::Version {
+                found: 1,
+                expected: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn legacy_data_is_not_an_envelope() {
+        let store = MemoryStore::default();
+
+        store.save("games", vec![1u64, 2, 3]).unwrap();
+
+        assert!(matches!(
+            store.load::<StateEnvelope>("games"),
+            Err(StoreError::Deserialize(_))
+        ));
+    }
+
+    #[test]
+    fn migrate_runs_every_step_up_to_the_current_version() {
+        let schema = schema("active_collectors").unwrap();
+
+        let value = migrate(&schema, 0, serde_json::json!([1, 2, 3])).unwrap();
+
+        assert_eq!(value, Value::Array(vec![]));
+        assert!(validate::<ActiveCollectors>(&value).is_ok());
+    }
+
+    #[test]
+    fn every_registered_key_accepts_its_default() {
+        for schema in registry() {
+            let value = match schema.key {
+                "admins" => serde_json::to_value(Admins::default()),
+                "games" => serde_json::to_value(Games::default()),
+                "active_collectors" => serde_json::to_value(ActiveCollectors::default()),
+                "role_backup" => serde_json::to_value(RoleBackups::default()),
+                "t_rooms" => serde_json::to_value(TRooms::default()),
+                "guild_apply" => serde_json::to_value(GuildApply::default()),
+                "state_history" => serde_json::to_value(StateHistory::default()),
+                "log_routes" => serde_json::to_value(LogRoutes::default()),
+                "voice_stats" => serde_json::to_value(VoiceStats::default()),
+                "invite_joins" => serde_json::to_value(InviteJoins::default()),
+                "quarantine" => serde_json::to_value(Quarantine::default()),
+                "sink_filters" => serde_json::to_value(SinkFilters::default()),
+                "member_history" => serde_json::to_value(MemberHistory::default()),
+                "member_snapshots" => serde_json::to_value(MemberSnapshots::default()),
+                "game_activity" => serde_json::to_value(GameActivity::default()),
+                key => panic!("no default listed for state key '{}'", key),
+            };
+
+            assert!(
+                (schema.validate)(&value.unwrap()).is_ok(),
+                "default of '{}' is rejected",
+                schema.key
+            );
+        }
+    }
+
+    #[test]
+    fn envelopes_round_trip_through_bincode() {
+        let (store, dir) = bincode_store("envelope");
+
+        store
+            .save_versioned("games", games(&[1, u64::MAX]))
+            .unwrap();
+        let loaded = store.load_versioned::<Games>("games");
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(role_ids(&loaded.unwrap()), vec![1, u64::MAX]);
+    }
+
+    #[test]
+    fn run_all_wraps_legacy_bincode_data() {
+        let (store, dir) = bincode_store("run-all");
+
+        // Snowflakes right after a length prefix, as a misread envelope would see them
+        store.save("games", games(&[1, 2])).unwrap();
+        store
+            .save(
+                "active_collectors",
+                vec![u64::MAX, 1_200_000_000_000_000_000],
+            )
+            .unwrap();
+
+        let first = run_all(&store);
+        let second = run_all(&store);
+        let games = store.load_versioned::<Games>("games");
+        let collectors = store.load_versioned::<ActiveCollectors>("active_collectors");
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(first.is_ok());
+        assert!(second.is_ok());
+        assert_eq!(role_ids(&games.unwrap()), vec![1, 2]);
+        assert!(collectors.unwrap().0.is_empty());
+    }
+}
@@ -0,0 +1,142 @@
+use super::history::{Origin, StateHistory};
+use crate::state::BotStateInitialization;
+use crate::utils::time::days_since;
+use crate::Data;
+use poise::serenity_prelude::{RoleId, User};
+use serde::{Deserialize, Serialize};
+
+const KEY: &str = "quarantine";
+
+/// New account quarantine settings and the alerts still waiting for a moderator
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct Quarantine {
+    /// `None` while the quarantine is turned off
+    pub settings: Option<QuarantineSettings>,
+    pub pending: Vec<Quarantined>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct QuarantineSettings {
+    pub min_account_age_days: i64,
+    pub role_id: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Quarantined {
+    /// The alert carrying the Approve / Kick / Ban buttons
+    pub message_id: u64,
+    pub user_id: u64,
+    /// Role to take away on approval, the settings may have changed since
+    pub role_id: u64,
+}
+
+// Main functionality
+impl Quarantine {
+    /// The role to give instead of the follower role, when the account is too new
+    pub fn role_for(data: &Data, user: &User) -> Result<Option<RoleId>, crate::Error> {
+        let Some(settings) = Self::load(data)?.settings else {
+            return Ok(None);
+        };
+
+        let age = days_since(&user.created_at());
+        match age < settings.min_account_age_days {
+            true => Ok(Some(RoleId(settings.role_id))),
+            false => Ok(None),
+        }
+    }
+
+    /// Replaces the settings, `None` turns the quarantine off
+    pub async fn configure(
+        data: &Data,
+        origin: &Origin,
+        settings: Option<QuarantineSettings>,
+    ) -> Result<bool, crate::Error> {
+        let before = Self::update(data, |quarantine| {
+            std::mem::replace(&mut quarantine.settings, settings)
+        })
+        .await?;
+
+        if before == settings {
+            return Ok(false);
+        }
+
+        StateHistory::record(data, origin, KEY, before, settings).await?;
+        Ok(true)
+    }
+
+    pub async fn add_pending<M: Into<u64>, U: Into<u64>, R: Into<u64>>(
+        data: &Data,
+        message_id: M,
+        user_id: U,
+        role_id: R,
+    ) -> Result<(), crate::Error> {
+        let quarantined = Quarantined {
+            message_id: message_id.into(),
+            user_id: user_id.into(),
+            role_id: role_id.into(),
+        };
+
+        Self::update(data, |quarantine| quarantine.pending.push(quarantined)).await?;
+        Ok(())
+    }
+
+    /// Removes and returns the member an alert is about, `None` if it was already handled
+    pub async fn take_pending<M: Into<u64>>(
+        data: &Data,
+        message_id: M,
+    ) -> Result<Option<Quarantined>, crate::Error> {
+        let message_id = message_id.into();
+
+        let quarantined = Self::update(data, |quarantine| {
+            let index = quarantine
+                .pending
+                .iter()
+                .position(|x| x.message_id == message_id)?;
+
+            Some(quarantine.pending.remove(index))
+        })
+        .await?;
+
+        Ok(quarantined)
+    }
+
+    /// Removes and returns every alert about a member, used when they leave before being handled
+    pub async fn take_pending_for_user<U: Into<u64>>(
+        data: &Data,
+        user_id: U,
+    ) -> Result<Vec<Quarantined>, crate::Error> {
+        let user_id = user_id.into();
+
+        let quarantined = Self::update(data, |quarantine| {
+            let (left, pending) = std::mem::take(&mut quarantine.pending)
+                .into_iter()
+                .partition(|x| x.user_id == user_id);
+            quarantine.pending = pending;
+
+            left
+        })
+        .await?;
+
+        Ok(quarantined)
+    }
+}
+
+// Core functionality
+impl Quarantine {
+    pub fn load(data: &Data) -> Result<Self, anyhow::Error>
+    where
+        for<'de> Self: Deserialize<'de>,
+    {
+        let data = data.state_cache.load::<Self>(&data.bot_state, KEY);
+        match data {
+            Ok(data) => Ok(data),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl BotStateInitialization for Quarantine {
+    fn get_key(&self) -> String {
+        KEY.to_string()
+    }
+}
@@ -1,7 +1,8 @@
+use super::history::{Origin, StateHistory};
 use crate::state::BotStateInitialization;
 use crate::Data;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{hash_map::Entry, HashMap};
 
 const KEY: &str = "role_backup";
 
@@ -9,59 +10,47 @@ const KEY: &str = "role_backup";
 pub struct RoleBackups(HashMap<u64, Vec<u64>>);
 
 impl RoleBackups {
-    pub fn load(data: &Data) -> Result<Self, anyhow::Error>
-    where
-        for<'de> Self: Deserialize<'de>,
-    {
-        let data = data.bot_state.load::<Self>(KEY);
-        match data {
-            Ok(data) => Ok(data),
-            Err(e) => Err(anyhow::anyhow!("{}", e)),
-        }
-    }
-
-    pub fn add<U: Into<u64>, R: Into<u64>>(
-        &mut self,
+    pub async fn add<U: Into<u64>, R: Into<u64> + Copy>(
         data: &Data,
+        origin: &Origin,
         user_id: U,
-        role_ids: &Vec<R>,
-    ) -> Result<bool, anyhow::Error>
-    where
-        R: Copy,
-    {
+        role_ids: &[R],
+    ) -> Result<bool, anyhow::Error> {
         let user_id: u64 = user_id.into();
-        let role_ids: Vec<u64> = role_ids.into_iter().map(|x| (*x).into()).collect();
-
-        if !self.0.contains_key(&user_id) {
-            self.0.insert(user_id, role_ids);
+        let role_ids: Vec<u64> = role_ids.iter().map(|x| (*x).into()).collect();
+        let entry = HashMap::from([(user_id, role_ids.clone())]);
 
-            let result = data.bot_state.save(&self.get_key(), self.clone());
-            match result {
-                Ok(_) => return Ok(true),
-                Err(e) => return Err(anyhow::anyhow!("{}", e)),
+        let added = Self::update(data, |state| match state.0.entry(user_id) {
+            Entry::Vacant(e) => {
+                e.insert(role_ids);
+                true
             }
+            Entry::Occupied(_) => false,
+        })
+        .await?;
+
+        if added {
+            StateHistory::record(data, origin, KEY, None::<u64>, entry).await?;
         }
 
-        Ok(false)
+        Ok(added)
     }
 
-    pub fn remove<U: Into<u64>>(
-        &mut self,
+    pub async fn remove<U: Into<u64>>(
         data: &Data,
+        origin: &Origin,
         user_id: U,
     ) -> Result<Option<Vec<u64>>, anyhow::Error> {
         let user_id: u64 = user_id.into();
 
-        if self.0.contains_key(&user_id) {
-            let return_data = self.0.remove(&user_id);
-            let result = data.bot_state.save(&self.get_key(), self.clone());
-            match result {
-                Ok(_) => return Ok(return_data),
-                Err(e) => return Err(anyhow::anyhow!("{}", e)),
-            }
+        let removed = Self::update(data, |state| state.0.remove(&user_id)).await?;
+
+        if let Some(role_ids) = &removed {
+            let entry = HashMap::from([(user_id, role_ids)]);
+            StateHistory::record(data, origin, KEY, entry, None::<u64>).await?;
         }
 
-        Ok(None)
+        Ok(removed)
     }
 }
 
@@ -0,0 +1,97 @@
+use super::history::{Origin, StateHistory};
+use super::log_routes::LogKind;
+use crate::state::BotStateInitialization;
+use crate::Data;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+const KEY: &str = "sink_filters";
+
+/// Every kind of structured event output, next to the Discord log channels
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    poise::ChoiceParameter,
+)]
+pub enum SinkKind {
+    #[name = "JSONL File"]
+    Jsonl,
+    #[name = "Webhook"]
+    Webhook,
+}
+
+/// Event kinds each sink skips, a sink without an entry receives every event
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct SinkFilters(pub BTreeMap<SinkKind, BTreeSet<LogKind>>);
+
+// Main functionality
+impl SinkFilters {
+    pub fn is_enabled(&self, sink: SinkKind, kind: LogKind) -> bool {
+        self.0
+            .get(&sink)
+            .map(|disabled| !disabled.contains(&kind))
+            .unwrap_or(true)
+    }
+
+    /// Turns an event kind on or off for a sink, returns false if it already was
+    pub async fn set(
+        data: &Data,
+        origin: &Origin,
+        sink: SinkKind,
+        kind: LogKind,
+        enabled: bool,
+    ) -> Result<bool, crate::Error> {
+        let changed = Self::update(data, |filters| {
+            let disabled = filters.0.entry(sink).or_default();
+            let changed = match enabled {
+                true => disabled.remove(&kind),
+                false => disabled.insert(kind),
+            };
+
+            if disabled.is_empty() {
+                filters.0.remove(&sink);
+            }
+
+            changed
+        })
+        .await?;
+
+        if changed {
+            let entry = BTreeMap::from([(sink, kind)]);
+            let (before, after) = match enabled {
+                true => (Some(entry), None),
+                false => (None, Some(entry)),
+            };
+            StateHistory::record(data, origin, KEY, before, after).await?;
+        }
+
+        Ok(changed)
+    }
+}
+
+// Core functionality
+impl SinkFilters {
+    pub fn load(data: &Data) -> Result<Self, anyhow::Error>
+    where
+        for<'de> Self: Deserialize<'de>,
+    {
+        let data = data.state_cache.load::<Self>(&data.bot_state, KEY);
+        match data {
+            Ok(data) => Ok(data),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl BotStateInitialization for SinkFilters {
+    fn get_key(&self) -> String {
+        KEY.to_string()
+    }
+}
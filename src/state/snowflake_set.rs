@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+/// Set of ids of a single snowflake type (`RoleId`, `UserId`, ...).
+///
+/// Serialized as a plain list of u64s, the same format the untyped `Vec<u64>` state used
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SnowflakeSet<T> {
+    ids: Vec<u64>,
+    #[serde(skip)]
+    kind: PhantomData<T>,
+}
+
+impl<T: Copy + Into<u64> + From<u64>> SnowflakeSet<T> {
+    pub fn contains(&self, id: &T) -> bool {
+        let id: u64 = (*id).into();
+        self.ids.contains(&id)
+    }
+
+    /// Returns false if the id was already in the set
+    pub fn insert(&mut self, id: T) -> bool {
+        if self.contains(&id) {
+            return false;
+        }
+
+        self.ids.push(id.into());
+        true
+    }
+
+    /// Returns false if the id was not in the set
+    pub fn remove(&mut self, id: &T) -> bool {
+        let id: u64 = (*id).into();
+
+        match self.ids.iter().position(|x| *x == id) {
+            Some(index) => {
+                self.ids.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        self.ids.iter().map(|x| T::from(*x))
+    }
+}
+
+impl<T: Copy + Into<u64> + From<u64>> FromIterator<T> for SnowflakeSet<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::default();
+        for id in iter {
+            set.insert(id);
+        }
+        set
+    }
+}
+
+// Manual impls, deriving would require `T` itself to implement them
+impl<T> Default for SnowflakeSet<T> {
+    fn default() -> Self {
+        Self {
+            ids: vec![],
+            kind: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for SnowflakeSet<T> {
+    fn clone(&self) -> Self {
+        Self {
+            ids: self.ids.clone(),
+            kind: PhantomData,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for SnowflakeSet<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.ids.iter()).finish()
+    }
+}
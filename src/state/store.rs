@@ -0,0 +1,287 @@
+use serde::{de::DeserializeOwned, Serialize};
+use shuttle_persist::{PersistError, PersistInstance};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, BufReader, BufWriter, Write},
+    path::PathBuf,
+    sync::Mutex,
+};
+
+/// Key / value storage that every piece of bot state is loaded from and saved to
+pub trait StateStore {
+    fn load<T: DeserializeOwned>(&self, key: &str) -> Result<T, StoreError>;
+    fn save<T: Serialize>(&self, key: &str, value: T) -> Result<(), StoreError>;
+}
+
+#[derive(Debug)]
+pub enum StoreError {
+    NotFound(String),
+    InvalidKey(String),
+    Unregistered(String),
+    Io(io::Error),
+    Serialize(String),
+    Deserialize(String),
+    Version {
+        key: String,
+        found: u32,
+        expected: u32,
+    },
+}
+
+impl std::fmt::Display for StoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound(key) => write!(f, "no state stored under key '{}'", key),
+            Self::InvalidKey(key) => write!(f, "invalid state key '{}'", key),
+            Self::Unregistered(key) => {
+                write!(f, "state key '{}' is not in the migration registry", key)
+            }
+            Self::Io(e) => write!(f, "state storage io error: {}", e),
+            Self::Serialize(e) => write!(f, "failed to serialize state: {}", e),
+            Self::Deserialize(e) => write!(f, "failed to deserialize state: {}", e),
+            Self::Version {
+                key,
+                found,
+                expected,
+            } => write!(
+                f,
+                "state key '{}' is at version {}, expected version {}",
+                key, found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+/// The storage backend selected at startup
+pub enum StateBackend {
+    Shuttle(PersistInstance),
+    JsonFile(JsonFileStore),
+    Memory(MemoryStore),
+}
+
+impl StateBackend {
+    /// Picks a backend from the optional `STATE_BACKEND` / `STATE_DIR` secrets,
+    /// falling back to shuttle_persist
+    pub fn from_config(
+        backend: Option<String>,
+        dir: Option<String>,
+        persist: PersistInstance,
+    ) -> Result<Self, anyhow::Error> {
+        let backend = backend.filter(|x| !x.is_empty());
+        let dir = dir.filter(|x| !x.is_empty());
+
+        match backend.as_deref() {
+            None | Some("shuttle") => Ok(Self::Shuttle(persist)),
+            Some("json") => {
+                let dir = dir.ok_or_else(|| {
+                    anyhow::anyhow!("'STATE_DIR' is required when STATE_BACKEND is 'json'")
+                })?;
+                Ok(Self::JsonFile(JsonFileStore::new(dir)?))
+            }
+            Some("memory") => Ok(Self::Memory(MemoryStore::default())),
+            Some(other) => Err(anyhow::anyhow!("Unknown STATE_BACKEND '{}'", other)),
+        }
+    }
+}
+
+impl StateStore for StateBackend {
+    fn load<T: DeserializeOwned>(&self, key: &str) -> Result<T, StoreError> {
+        match self {
+            Self::Shuttle(store) => StateStore::load(store, key),
+            Self::JsonFile(store) => store.load(key),
+            Self::Memory(store) => store.load(key),
+        }
+    }
+
+    fn save<T: Serialize>(&self, key: &str, value: T) -> Result<(), StoreError> {
+        match self {
+            Self::Shuttle(store) => StateStore::save(store, key, value),
+            Self::JsonFile(store) => store.save(key, value),
+            Self::Memory(store) => store.save(key, value),
+        }
+    }
+}
+
+impl StateStore for PersistInstance {
+    fn load<T: DeserializeOwned>(&self, key: &str) -> Result<T, StoreError> {
+        PersistInstance::load(self, key).map_err(|e| persist_error(key, e))
+    }
+
+    fn save<T: Serialize>(&self, key: &str, value: T) -> Result<(), StoreError> {
+        PersistInstance::save(self, key, value).map_err(|e| persist_error(key, e))
+    }
+}
+
+fn persist_error(key: &str, error: PersistError) -> StoreError {
+    match error {
+        PersistError::Open(e) if e.kind() == io::ErrorKind::NotFound => {
+            StoreError::NotFound(key.to_string())
+        }
+        PersistError::InvalidKey => StoreError::InvalidKey(key.to_string()),
+        PersistError::Open(e)
+        | PersistError::CreateFolder(e)
+        | PersistError::ListFolder(e)
+        | PersistError::RemoveFolder(e)
+        | PersistError::RemoveFile(e) => StoreError::Io(e),
+        PersistError::ListName(e) => StoreError::Io(io::Error::other(e)),
+        PersistError::Serialize(e) => StoreError::Serialize(e.to_string()),
+        PersistError::Deserialize(e) => StoreError::Deserialize(e.to_string()),
+    }
+}
+
+/// Stores every key as a pretty printed `<key>.json` file inside `dir`
+pub struct JsonFileStore {
+    dir: PathBuf,
+}
+
+impl JsonFileStore {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Result<Self, StoreError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).map_err(StoreError::Io)?;
+
+        Ok(Self { dir })
+    }
+
+    fn get_storage_file(&self, key: &str) -> Result<PathBuf, StoreError> {
+        let path = self.dir.join(format!("{key}.json"));
+        if path.parent() != Some(self.dir.as_path()) {
+            Err(StoreError::InvalidKey(key.to_string()))
+        } else {
+            Ok(path)
+        }
+    }
+}
+
+impl StateStore for JsonFileStore {
+    fn load<T: DeserializeOwned>(&self, key: &str) -> Result<T, StoreError> {
+        let path = self.get_storage_file(key)?;
+        let file = fs::File::open(path).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => StoreError::NotFound(key.to_string()),
+            _ => StoreError::Io(e),
+        })?;
+
+        serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| StoreError::Deserialize(e.to_string()))
+    }
+
+    fn save<T: Serialize>(&self, key: &str, value: T) -> Result<(), StoreError> {
+        let path = self.get_storage_file(key)?;
+
+        // Write to a temporary file first so a crash never leaves a half written key behind
+        let temp_path = path.with_extension("json.tmp");
+        let file = fs::File::create(&temp_path).map_err(StoreError::Io)?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, &value)
+            .map_err(|e| StoreError::Serialize(e.to_string()))?;
+
+        // The rename is only atomic if the data reached the disk before it
+        writer.flush().map_err(StoreError::Io)?;
+        writer.get_ref().sync_all().map_err(StoreError::Io)?;
+
+        fs::rename(temp_path, path).map_err(StoreError::Io)
+    }
+}
+
+/// Keeps every key in memory only, nothing survives a restart
+#[derive(Default)]
+pub struct MemoryStore {
+    values: Mutex<HashMap<String, serde_json::Value>>,
+}
+
+impl StateStore for MemoryStore {
+    fn load<T: DeserializeOwned>(&self, key: &str) -> Result<T, StoreError> {
+        let values = self.values.lock().expect("MemoryStore mutex poisoned");
+        let value = values
+            .get(key)
+            .cloned()
+            .ok_or_else(|| StoreError::NotFound(key.to_string()))?;
+
+        serde_json::from_value(value).map_err(|e| StoreError::Deserialize(e.to_string()))
+    }
+
+    fn save<T: Serialize>(&self, key: &str, value: T) -> Result<(), StoreError> {
+        let value =
+            serde_json::to_value(value).map_err(|e| StoreError::Serialize(e.to_string()))?;
+
+        let mut values = self.values.lock().expect("MemoryStore mutex poisoned");
+        values.insert(key.to_string(), value);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Sample {
+        ids: Vec<u64>,
+        name: String,
+    }
+
+    fn sample() -> Sample {
+        Sample {
+            ids: vec![1, 2, 3],
+            name: "rooms".to_string(),
+        }
+    }
+
+    #[test]
+    fn memory_store_round_trips() {
+        let store = StateBackend::Memory(MemoryStore::default());
+
+        store.save("sample", sample()).unwrap();
+
+        assert_eq!(store.load::<Sample>("sample").unwrap(), sample());
+    }
+
+    #[test]
+    fn memory_store_overwrites_keys() {
+        let store = StateBackend::Memory(MemoryStore::default());
+
+        store.save("sample", sample()).unwrap();
+        store.save("sample", Vec::<u64>::new()).unwrap();
+
+        assert!(store.load::<Vec<u64>>("sample").unwrap().is_empty());
+    }
+
+    #[test]
+    fn missing_key_is_not_found() {
+        let store = StateBackend::Memory(MemoryStore::default());
+
+        let result = store.load::<Sample>("missing");
+
+        assert!(matches!(result, Err(StoreError::NotFound(key)) if key == "missing"));
+    }
+
+    #[test]
+    fn wrong_shape_is_a_deserialize_error() {
+        let store = StateBackend::Memory(MemoryStore::default());
+
+        store.save("sample", "not a sample").unwrap();
+
+        assert!(matches!(
+            store.load::<Sample>("sample"),
+            Err(StoreError::Deserialize(_))
+        ));
+    }
+
+    #[test]
+    fn json_file_store_round_trips() {
+        let dir = std::env::temp_dir().join(format!("state-store-test-{}", std::process::id()));
+        let store = JsonFileStore::new(&dir).unwrap();
+
+        store.save("sample", sample()).unwrap();
+        let loaded = store.load::<Sample>("sample");
+        let leftover = dir.join("sample.json.tmp").exists();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(loaded.unwrap(), sample());
+        assert!(!leftover);
+    }
+}
@@ -1,5 +1,6 @@
 use super::{BotStateInitialization, Data};
-use poise::serenity_prelude::RoleId;
+use crate::extensions::InteractiveSnowflakeExt;
+use poise::serenity_prelude::{ChannelId, RoleId};
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
@@ -16,10 +17,11 @@ pub struct Room {
 }
 
 impl Room {
-    pub fn new<R: Into<u64>, C: Into<u64>>(role_id: R, channel_id: C, is_open: bool) -> Self
-    where
-        R: Copy,
-    {
+    pub fn new<R: Into<u64> + Copy, C: Into<u64>>(
+        role_id: R,
+        channel_id: C,
+        is_open: bool,
+    ) -> Self {
         let role_id: u64 = role_id.into();
         let channel_id: u64 = channel_id.into();
         Self {
@@ -32,32 +34,48 @@ impl Room {
     pub fn toggle_open(&mut self) {
         self.is_open = !self.is_open;
     }
+
+    /// Channel mention followed by its role, for log embeds
+    pub fn describe(&self) -> String {
+        format!(
+            "{} ({})",
+            ChannelId(self.channel_id).get_interactive(),
+            RoleId(self.role_id).get_interactive()
+        )
+    }
 }
 
-// Main functionality
-impl TRooms {
-    pub fn find_open_room(&mut self, data: &Data) -> Result<Option<(u64, u64)>, crate::Error> {
-        let mut toggle = false;
-        let mut index: usize = 0;
-
-        for (i, room) in self.0.iter().enumerate() {
-            if room.is_open {
-                toggle = true;
-                index = i;
-                break;
-            }
-        }
+/// What startup reconciliation changed compared to the stored rooms
+#[derive(Clone, Default, Debug)]
+pub struct RoomChanges {
+    pub added: Vec<Room>,
+    pub removed: Vec<Room>,
+    pub role_changed: Vec<Room>,
+    pub duplicates_dropped: usize,
+}
 
-        if toggle {
-            let rooms = &mut self.0;
-            rooms[index].toggle_open();
+impl RoomChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.role_changed.is_empty()
+            && self.duplicates_dropped == 0
+    }
+}
 
-            self.save(data)?;
+// Main functionality
+impl TRooms {
+    /// Claims the first open room, closing it in the same update so no one else can grab it
+    pub async fn find_open_room(data: &Data) -> Result<Option<(u64, u64)>, crate::Error> {
+        let open_room = Self::update(data, |t_rooms| {
+            let room = t_rooms.0.iter_mut().find(|room| room.is_open)?;
+            room.toggle_open();
 
-            return Ok(Some((self.0[index].role_id, self.0[index].channel_id)));
-        }
+            Some((room.role_id, room.channel_id))
+        })
+        .await?;
 
-        Ok(None)
+        Ok(open_room)
     }
 
     pub fn find_room<R: Into<u64>>(
@@ -75,70 +93,99 @@ impl TRooms {
 
         Ok(None)
     }
-
-    pub fn save(&self, data: &Data) -> Result<(), anyhow::Error> {
-        let result = data.bot_state.save(&self.get_key(), self.clone());
-        match result {
-            Ok(_) => Ok(()),
-            Err(e) => return Err(anyhow::anyhow!("{}", e)),
-        }
-    }
 }
 
 // Core functionality
 impl TRooms {
+    /// Whether any room uses `id` as its role or channel
+    pub fn mentions(&self, id: u64) -> bool {
+        self.0.iter().any(|x| x.role_id == id || x.channel_id == id)
+    }
+
+    /// Makes the stored rooms match T_ROLES / T_ROOMS.
+    ///
+    /// Rooms are matched by channel, so a room that is still configured keeps
+    /// whether it is open, new rooms start open and unconfigured rooms are dropped
+    pub async fn reconcile(data: &Data) -> Result<RoomChanges, anyhow::Error> {
+        let mut configured: Vec<(u64, u64)> = vec![];
+
+        for (role_id, channel_id) in data.t_ids.iter() {
+            let role_id = RoleId::from_str(role_id.trim()).map_err(|e| {
+                anyhow::anyhow!("'{}' in T_ROLES is not a valid id: {}", role_id, e)
+            })?;
+            let channel_id = ChannelId::from_str(channel_id.trim()).map_err(|e| {
+                anyhow::anyhow!("'{}' in T_ROOMS is not a valid id: {}", channel_id, e)
+            })?;
+
+            configured.push((role_id.0, channel_id.0));
+        }
+
+        let changes = Self::update(data, |t_rooms| {
+            let mut changes = RoomChanges::default();
+            let mut rooms: Vec<Room> = vec![];
+
+            for (role_id, channel_id) in configured {
+                if rooms.iter().any(|room| room.channel_id == channel_id) {
+                    continue;
+                }
+
+                let existing = t_rooms.0.iter().find(|room| room.channel_id == channel_id);
+
+                let room = match existing {
+                    Some(existing) if existing.role_id != role_id => {
+                        let room = Room::new(role_id, channel_id, existing.is_open);
+                        changes.role_changed.push(room.clone());
+                        room
+                    }
+                    Some(existing) => existing.clone(),
+                    None => {
+                        let room = Room::new(role_id, channel_id, true);
+                        changes.added.push(room.clone());
+                        room
+                    }
+                };
+
+                rooms.push(room);
+            }
+
+            for room in t_rooms.0.iter() {
+                let configured = rooms.iter().any(|x| x.channel_id == room.channel_id);
+                let reported = changes
+                    .removed
+                    .iter()
+                    .any(|x| x.channel_id == room.channel_id);
+
+                if !configured && !reported {
+                    changes.removed.push(room.clone());
+                }
+            }
+
+            // Older versions appended the configured rooms on every boot
+            let kept = rooms.len() - changes.added.len();
+            changes.duplicates_dropped = t_rooms.0.len() - kept - changes.removed.len();
+            t_rooms.0 = rooms;
+
+            changes
+        })
+        .await?;
+
+        Ok(changes)
+    }
+
     pub fn load(data: &Data) -> Result<Self, anyhow::Error>
     where
         for<'de> Self: Deserialize<'de>,
     {
-        let data = data.bot_state.load::<Self>(KEY);
+        let data = data.state_cache.load::<Self>(&data.bot_state, KEY);
         match data {
-            Ok(data) => return Ok(data),
-            Err(e) => return Err(anyhow::anyhow!("{}", e)),
+            Ok(data) => Ok(data),
+            Err(e) => Err(e.into()),
         }
     }
-
-    fn add(&mut self, data: &Data, room: Room) -> Result<(), crate::Error> {
-        self.0.push(room);
-
-        self.save(data)?;
-
-        Ok(())
-    }
 }
 
 impl BotStateInitialization for TRooms {
     fn get_key(&self) -> String {
         KEY.to_string()
     }
-
-    fn init_state(data: &Data) -> Result<(), anyhow::Error>
-    where
-        for<'de> Self: Deserialize<'de>,
-        Self: Serialize,
-    {
-        let mut data_struct = Self::default();
-        let result = data_struct.init_state_inner::<Self>(&data);
-        match result {
-            Ok(_) => (),
-            Err(e) => return Err(anyhow::anyhow!("{}", e)),
-        };
-
-        data.t_ids
-            .clone()
-            .into_iter()
-            .map(|x| {
-                (
-                    RoleId::from_str(x.0.as_str()).unwrap(),
-                    RoleId::from_str(x.1.as_str()).unwrap(),
-                )
-            })
-            .for_each(|ids| {
-                data_struct
-                    .add(data, Room::new(ids.0, ids.1, true))
-                    .unwrap();
-            });
-
-        Ok(())
-    }
 }
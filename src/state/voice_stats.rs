@@ -0,0 +1,231 @@
+use crate::state::BotStateInitialization;
+use crate::Data;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+const KEY: &str = "voice_stats";
+
+const SECONDS_PER_DAY: i64 = 86400;
+
+/// Finished time older than this many days is dropped, `/voice_stats` can't look further back
+pub const RETENTION_DAYS: i64 = 366;
+
+/// Finished seconds by day, user and channel
+pub type DailyTotals = BTreeMap<i64, BTreeMap<u64, BTreeMap<u64, i64>>>;
+
+/// Voice sessions in progress and the time spent in each channel so far
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct VoiceStats {
+    /// Keyed by user id
+    pub open: BTreeMap<u64, OpenSession>,
+    /// Finished time, keyed by days since the unix epoch (UTC), then user and channel
+    pub days: DailyTotals,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OpenSession {
+    /// Unix timestamp in seconds of the join, kept across moves
+    pub joined_at: i64,
+    pub channel_id: u64,
+    /// Unix timestamp in seconds of the join or the last move
+    pub channel_since: i64,
+}
+
+/// Which finished time a leaderboard counts, `None` matches everything
+#[derive(Default)]
+pub struct StatsFilter {
+    pub user_id: Option<u64>,
+    pub channel_id: Option<u64>,
+    /// Inclusive, in days since the unix epoch
+    pub from_day: Option<i64>,
+    /// Inclusive, in days since the unix epoch
+    pub to_day: Option<i64>,
+}
+
+pub struct Leaderboard {
+    pub total: i64,
+    /// Seconds per user, most time first
+    pub users: Vec<(u64, i64)>,
+    /// Seconds per channel, most time first
+    pub channels: Vec<(u64, i64)>,
+}
+
+// Main functionality
+impl VoiceStats {
+    /// Opens a session for a user that just joined a voice channel
+    pub async fn join(
+        data: &Data,
+        user_id: u64,
+        channel_id: u64,
+        now: i64,
+    ) -> Result<(), crate::Error> {
+        Self::update(data, |stats| {
+            // The leave was missed while the bot was offline, its length is unknown
+            if stats.open.remove(&user_id).is_some() {
+                tracing::warn!("Dropped a stale voice session of user {}", user_id);
+            }
+
+            stats.open.insert(
+                user_id,
+                OpenSession {
+                    joined_at: now,
+                    channel_id,
+                    channel_since: now,
+                },
+            );
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// Counts the time spent in the old channel and carries the session over to the new one
+    pub async fn moved(
+        data: &Data,
+        user_id: u64,
+        channel_id: u64,
+        now: i64,
+    ) -> Result<(), crate::Error> {
+        Self::update(data, |stats| {
+            let Some(session) = stats.open.get(&user_id).cloned() else {
+                // Joined before tracking started, count from here on
+                stats.open.insert(
+                    user_id,
+                    OpenSession {
+                        joined_at: now,
+                        channel_id,
+                        channel_since: now,
+                    },
+                );
+                return;
+            };
+
+            stats.add(user_id, session.channel_id, session.channel_since, now);
+            stats.open.insert(
+                user_id,
+                OpenSession {
+                    channel_id,
+                    channel_since: now,
+                    ..session
+                },
+            );
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// Closes the user's session, returning its length in seconds when its start is known
+    pub async fn leave(data: &Data, user_id: u64, now: i64) -> Result<Option<i64>, crate::Error> {
+        let duration = Self::update(data, |stats| {
+            let session = stats.open.remove(&user_id)?;
+            stats.add(user_id, session.channel_id, session.channel_since, now);
+
+            Some(now - session.joined_at)
+        })
+        .await?;
+
+        Ok(duration)
+    }
+
+    pub fn leaderboard(&self, filter: &StatsFilter) -> Leaderboard {
+        let mut total = 0;
+        let mut users: HashMap<u64, i64> = HashMap::new();
+        let mut channels: HashMap<u64, i64> = HashMap::new();
+
+        let from = filter.from_day.unwrap_or(i64::MIN);
+        let to = filter.to_day.unwrap_or(i64::MAX);
+        // `range` panics on an inverted range
+        if from > to {
+            return Leaderboard {
+                total: 0,
+                users: vec![],
+                channels: vec![],
+            };
+        }
+
+        for users_on_day in self.days.range(from..=to).map(|(_, x)| x) {
+            let matching = users_on_day
+                .iter()
+                .filter(|(user_id, _)| filter.user_id.map(|id| **user_id == id).unwrap_or(true))
+                .flat_map(|(user_id, channels)| channels.iter().map(move |x| (*user_id, x)))
+                .filter(|(_, (channel_id, _))| {
+                    filter
+                        .channel_id
+                        .map(|id| **channel_id == id)
+                        .unwrap_or(true)
+                });
+
+            for (user_id, (channel_id, seconds)) in matching {
+                total += seconds;
+                *users.entry(user_id).or_default() += seconds;
+                *channels.entry(*channel_id).or_default() += seconds;
+            }
+        }
+
+        Leaderboard {
+            total,
+            users: sorted(users),
+            channels: sorted(channels),
+        }
+    }
+
+    /// Adds the time between `from` and `to`, split at UTC midnight, and drops expired days
+    fn add(&mut self, user_id: u64, channel_id: u64, from: i64, to: i64) {
+        let mut start = from;
+
+        while start < to {
+            let day = day_of(start);
+            let end = to.min((day + 1) * SECONDS_PER_DAY);
+
+            *self
+                .days
+                .entry(day)
+                .or_default()
+                .entry(user_id)
+                .or_default()
+                .entry(channel_id)
+                .or_default() += end - start;
+
+            start = end;
+        }
+
+        self.days = self.days.split_off(&oldest_day(to));
+    }
+}
+
+// Core functionality
+impl VoiceStats {
+    pub fn load(data: &Data) -> Result<Self, anyhow::Error>
+    where
+        for<'de> Self: Deserialize<'de>,
+    {
+        let data = data.state_cache.load::<Self>(&data.bot_state, KEY);
+        match data {
+            Ok(data) => Ok(data),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl BotStateInitialization for VoiceStats {
+    fn get_key(&self) -> String {
+        KEY.to_string()
+    }
+}
+
+/// Converts a unix timestamp in seconds to days since the unix epoch
+pub fn day_of(timestamp: i64) -> i64 {
+    timestamp.div_euclid(SECONDS_PER_DAY)
+}
+
+/// First day still kept at `now`, a unix timestamp in seconds
+pub fn oldest_day(now: i64) -> i64 {
+    day_of(now) - RETENTION_DAYS
+}
+
+fn sorted(totals: HashMap<u64, i64>) -> Vec<(u64, i64)> {
+    let mut totals: Vec<(u64, i64)> = totals.into_iter().collect();
+    totals.sort_by_key(|x| std::cmp::Reverse(x.1));
+    totals
+}
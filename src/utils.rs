@@ -35,7 +35,7 @@ pub mod time {
             .and_hms_opt(0, 0, 0)
             .expect("date_diff: and_hms_opt failed");
 
-        let datetime = DateTime::<Utc>::from_utc(date, Utc);
+        let datetime = DateTime::<Utc>::from_naive_utc_and_offset(date, Utc);
 
         let diff = today.signed_duration_since(datetime);
         let days = diff.num_days();
@@ -46,4 +46,64 @@ pub mod time {
 
         format!("{} years {} months {} days", years, months, days)
     }
+
+    /// Whole days between `date` and now
+    pub fn days_since(date: &Timestamp) -> i64 {
+        let diff = Utc::now().timestamp() - date.unix_timestamp();
+        diff / 86400
+    }
+
+    /// Formats a duration in seconds as e.g. `2h 5m 10s`, leaving out leading zero units
+    pub fn format_duration(seconds: i64) -> String {
+        let hours = seconds / 3600;
+        let minutes = seconds % 3600 / 60;
+        let seconds = seconds % 60;
+
+        match (hours, minutes) {
+            (0, 0) => format!("{}s", seconds),
+            (0, _) => format!("{}m {}s", minutes, seconds),
+            _ => format!("{}h {}m {}s", hours, minutes, seconds),
+        }
+    }
+}
+
+pub mod embed {
+    use crate::extensions::InteractiveSnowflakeExt;
+    use poise::serenity_prelude::{ChannelId, CreateEmbedFooter};
+
+    /// Embed field values are capped at 1024 characters by Discord
+    pub const FIELD_LIMIT: usize = 1024;
+
+    /// Embed descriptions are capped at 4096 characters by Discord
+    pub const DESCRIPTION_LIMIT: usize = 4096;
+
+    /// Cuts a value down to fit in an embed field
+    pub fn truncate(value: &str) -> String {
+        truncate_to(value, FIELD_LIMIT)
+    }
+
+    /// Cuts a value down to `limit` characters, ending it with `…` if anything was dropped
+    pub fn truncate_to(value: &str, limit: usize) -> String {
+        if value.chars().count() <= limit {
+            return value.to_string();
+        }
+
+        let mut value: String = value.chars().take(limit - 1).collect();
+        value.push('…');
+        value
+    }
+
+    /// Mention of the category of a channel, or the channel a thread belongs to
+    pub fn describe_parent(parent_id: Option<ChannelId>) -> String {
+        match parent_id {
+            Some(parent_id) => parent_id.get_interactive(),
+            None => "*None*".to_string(),
+        }
+    }
+
+    pub fn footer<S: ToString>(text: S) -> CreateEmbedFooter {
+        let mut footer = CreateEmbedFooter::default();
+        footer.text(text);
+        footer
+    }
 }